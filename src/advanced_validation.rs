@@ -0,0 +1,49 @@
+//! # Advanced request validation
+//!
+//! Deep content inspection beyond `validation::InputValidator`'s structural
+//! checks. Currently just bounded decompression: decodes a (possibly
+//! compressed) request body via `crate::content_type::ContentTypeValidator`
+//! and stashes the decoded text on `SecurityContext` so
+//! `crate::threats::RuleEngine` can scan the real payload instead of its
+//! compressed bytes.
+
+use crate::config::AdvancedValidationConfig;
+use crate::content_type::ContentTypeValidator;
+use crate::core::{SecurityContext, SecurityResult};
+use http::Request;
+
+pub struct AdvancedValidator {
+    content_type: ContentTypeValidator,
+}
+
+impl AdvancedValidator {
+    pub fn new(config: &AdvancedValidationConfig) -> Self {
+        Self {
+            content_type: ContentTypeValidator::new(config),
+        }
+    }
+
+    /// Decode `request`'s body per its `Content-Encoding` and record it on
+    /// `context` for later pipeline stages.
+    ///
+    /// A body that isn't valid UTF-8 after decoding is left out of
+    /// `context.decoded_body` rather than rejected outright - binary payloads
+    /// are legitimate, and the rule engine's substring/regex matchers only
+    /// make sense against text anyway.
+    pub async fn validate<B: AsRef<[u8]>>(
+        &self,
+        request: &Request<B>,
+        context: &mut SecurityContext,
+    ) -> SecurityResult<()> {
+        let content_encoding = request
+            .headers()
+            .get(http::header::CONTENT_ENCODING)
+            .and_then(|h| h.to_str().ok());
+
+        let decoded = self
+            .content_type
+            .decode(content_encoding, request.body().as_ref())?;
+        context.decoded_body = String::from_utf8(decoded).ok();
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+//! # Audit logging
+//!
+//! Every allow/block decision `SecurityLayer::process_request` makes is
+//! offered to a pluggable [`AuditSink`]. Sinks are fire-and-forget: emitting
+//! an event must never block or slow down the security pipeline, so
+//! implementations buffer onto a channel and ship from a background task.
+
+use crate::core::{SecurityContext, SecurityError, ThreatSeverity};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A single structured decision made by the security pipeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub request_id: String,
+    pub client_ip: String,
+    pub user_id: Option<String>,
+    pub roles: Vec<String>,
+    pub threat_score: u32,
+    pub metadata: HashMap<String, String>,
+    /// `"allowed"` or `"blocked"`.
+    pub outcome: &'static str,
+    /// The `SecurityError` variant name, if the decision was a rejection.
+    pub error_variant: Option<&'static str>,
+    pub severity: Option<ThreatSeverity>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl AuditEvent {
+    /// Build an audit event for an allowed request.
+    pub fn allowed(context: &SecurityContext, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            request_id: context.request_id.clone(),
+            client_ip: context.client_ip.clone(),
+            user_id: context.user_id.clone(),
+            roles: context.roles.clone(),
+            threat_score: context.threat_score,
+            metadata: context.metadata.clone(),
+            outcome: "allowed",
+            error_variant: None,
+            severity: None,
+            timestamp,
+        }
+    }
+
+    /// Build an audit event for a blocked request.
+    pub fn blocked(context: &SecurityContext, error: &SecurityError, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            request_id: context.request_id.clone(),
+            client_ip: context.client_ip.clone(),
+            user_id: context.user_id.clone(),
+            roles: context.roles.clone(),
+            threat_score: context.threat_score,
+            metadata: context.metadata.clone(),
+            outcome: "blocked",
+            error_variant: Some(error.variant_name()),
+            severity: Some(error.severity()),
+            timestamp,
+        }
+    }
+}
+
+/// A pluggable destination for audit events.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn emit(&self, event: AuditEvent);
+}
+
+/// Discards every event. Used in tests; a `SecurityLayer` with no sink
+/// attached via `SecurityLayer::with_audit_sink` emits no events at all.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopAuditSink;
+
+#[async_trait]
+impl AuditSink for NoopAuditSink {
+    async fn emit(&self, _event: AuditEvent) {}
+}
+
+pub mod kafka {
+    //! Kafka-backed [`AuditSink`](super::AuditSink).
+
+    use super::{AuditEvent, AuditSink};
+    use async_trait::async_trait;
+    use rdkafka::message::{Header, OwnedHeaders};
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+    use rdkafka::ClientConfig;
+    use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tracing::warn;
+
+    /// Configuration for [`KafkaAuditSink`].
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct KafkaAuditConfig {
+        pub brokers: String,
+        pub topic: String,
+        /// Capacity of the in-process buffer between `emit` and the producer
+        /// task. Once full, `emit` drops the event rather than block.
+        #[serde(default = "KafkaAuditConfig::default_buffer_size")]
+        pub buffer_size: usize,
+    }
+
+    impl KafkaAuditConfig {
+        fn default_buffer_size() -> usize {
+            10_000
+        }
+    }
+
+    /// Produces one Kafka message per audit event, keyed by `client_ip`.
+    pub struct KafkaAuditSink {
+        tx: mpsc::Sender<AuditEvent>,
+    }
+
+    impl KafkaAuditSink {
+        pub fn new(config: KafkaAuditConfig) -> Self {
+            let (tx, mut rx) = mpsc::channel::<AuditEvent>(config.buffer_size);
+
+            let producer: FutureProducer = ClientConfig::new()
+                .set("bootstrap.servers", &config.brokers)
+                .create()
+                .expect("failed to create kafka audit producer");
+            let topic = config.topic.clone();
+
+            tokio::spawn(async move {
+                while let Some(event) = rx.recv().await {
+                    let payload = match serde_json::to_vec(&event) {
+                        Ok(payload) => payload,
+                        Err(err) => {
+                            warn!(error = %err, "audit: failed to serialize event");
+                            continue;
+                        }
+                    };
+
+                    let mut headers = OwnedHeaders::new();
+                    for (key, value) in &event.metadata {
+                        headers = headers.insert(Header {
+                            key,
+                            value: Some(value.as_bytes()),
+                        });
+                    }
+
+                    let record = FutureRecord::to(&topic)
+                        .key(&event.client_ip)
+                        .payload(&payload)
+                        .headers(headers);
+
+                    // Fire-and-forget: a send failure only gets logged, never
+                    // propagated back into the security pipeline.
+                    if let Err((err, _)) = producer.send(record, Duration::from_secs(0)).await {
+                        warn!(error = %err, "audit: failed to publish to kafka");
+                    }
+                }
+            });
+
+            Self { tx }
+        }
+    }
+
+    #[async_trait]
+    impl AuditSink for KafkaAuditSink {
+        async fn emit(&self, event: AuditEvent) {
+            if self.tx.try_send(event).is_err() {
+                warn!("audit: buffer full, dropping event");
+            }
+        }
+    }
+}
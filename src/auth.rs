@@ -0,0 +1,336 @@
+//! # Authentication
+//!
+//! Resolves an internal user context from an incoming request. Two bearer
+//! schemes are accepted: the pre-existing opaque internal token, and
+//! federated OIDC/OAuth2 JWTs verified against a configured set of trusted
+//! issuers (see [`oidc`]).
+
+use crate::config::AuthConfig;
+use crate::core::{SecurityError, SecurityResult};
+use http::Request;
+
+/// The identity resolved for an authenticated request.
+#[derive(Debug, Clone)]
+pub struct UserContext {
+    pub user_id: String,
+    pub roles: Vec<String>,
+}
+
+/// Resolves request identity for the security pipeline.
+pub struct AuthManager {
+    config: AuthConfig,
+    oidc: oidc::OidcVerifier,
+}
+
+impl AuthManager {
+    pub fn new(config: AuthConfig) -> Self {
+        let oidc = oidc::OidcVerifier::new(config.oidc_providers.clone());
+        Self { config, oidc }
+    }
+
+    /// Attempt to authenticate `request`.
+    ///
+    /// Returns `Ok(None)` for an anonymous request (the caller decides
+    /// whether that's acceptable via `require_auth`), `Ok(Some(_))` once an
+    /// identity is resolved, or `Err` if credentials were present but invalid.
+    pub async fn authenticate<B>(&self, request: &Request<B>) -> SecurityResult<Option<UserContext>> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+
+        let Some(auth_header) = request
+            .headers()
+            .get(http::header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+        else {
+            return Ok(None);
+        };
+
+        let Some(token) = auth_header.strip_prefix("Bearer ") else {
+            return Err(SecurityError::AuthenticationFailed(
+                "unsupported authorization scheme".to_string(),
+            ));
+        };
+
+        if token.is_empty() {
+            return Err(SecurityError::AuthenticationFailed(
+                "empty bearer token".to_string(),
+            ));
+        }
+
+        // Once OIDC providers are configured, every bearer token must verify
+        // as a JWT against them - falling back to the trivial internal scheme
+        // for anything that doesn't parse as one would let an attacker skip
+        // verification entirely by sending a token with the "wrong" shape.
+        if !self.config.oidc_providers.is_empty() {
+            if token.matches('.').count() != 2 {
+                return Err(SecurityError::AuthenticationFailed(
+                    "expected a JWT bearer token".to_string(),
+                ));
+            }
+            return self.oidc.verify(token).await.map(Some);
+        }
+
+        // No OIDC providers configured: fall back to the internal
+        // opaque-token scheme.
+        Ok(Some(UserContext {
+            user_id: token.to_string(),
+            roles: Vec::new(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AuthConfig, OidcProviderConfig};
+
+    fn oidc_provider() -> OidcProviderConfig {
+        OidcProviderConfig {
+            issuer: "https://issuer.example".to_string(),
+            jwks_uri: "https://issuer.example/.well-known/jwks.json".to_string(),
+            audience: "my-api".to_string(),
+            user_id_claim: "sub".to_string(),
+            roles_claim: "roles".to_string(),
+            jwks_ttl_secs: 3600,
+        }
+    }
+
+    fn request_with_bearer(token: &str) -> Request<()> {
+        Request::builder()
+            .header(http::header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rejects_non_jwt_bearer_when_oidc_is_configured() {
+        let manager = AuthManager::new(AuthConfig {
+            enabled: true,
+            require_auth: false,
+            oidc_providers: vec![oidc_provider()],
+        });
+
+        let request = request_with_bearer("opaque-internal-token");
+        let result = manager.authenticate(&request).await;
+
+        assert!(matches!(result, Err(SecurityError::AuthenticationFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_internal_scheme_without_oidc_providers() {
+        let manager = AuthManager::new(AuthConfig {
+            enabled: true,
+            require_auth: false,
+            oidc_providers: vec![],
+        });
+
+        let request = request_with_bearer("opaque-internal-token");
+        let user = manager.authenticate(&request).await.unwrap().unwrap();
+
+        assert_eq!(user.user_id, "opaque-internal-token");
+        assert!(user.roles.is_empty());
+    }
+}
+
+pub mod oidc {
+    //! OIDC/OAuth2 bearer-token verification against trusted issuers.
+
+    use super::UserContext;
+    use crate::config::OidcProviderConfig;
+    use crate::core::SecurityError;
+    use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet};
+    use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+    use tokio::sync::RwLock;
+
+    struct CachedJwks {
+        jwks: JwkSet,
+        fetched_at: Instant,
+    }
+
+    /// Verifies bearer JWTs against a set of trusted OIDC issuers, caching
+    /// each issuer's JWKS keyed by `kid` and refreshing on a cache miss (a
+    /// `kid` we haven't seen, e.g. after key rotation) or once the cached
+    /// set goes stale.
+    pub struct OidcVerifier {
+        providers: Vec<OidcProviderConfig>,
+        jwks_cache: RwLock<HashMap<String, CachedJwks>>,
+        http: reqwest::Client,
+    }
+
+    impl OidcVerifier {
+        pub fn new(providers: Vec<OidcProviderConfig>) -> Self {
+            Self {
+                providers,
+                jwks_cache: RwLock::new(HashMap::new()),
+                http: reqwest::Client::new(),
+            }
+        }
+
+        pub async fn verify(&self, token: &str) -> Result<UserContext, SecurityError> {
+            let header = decode_header(token).map_err(|err| {
+                SecurityError::AuthenticationFailed(format!("malformed jwt header: {err}"))
+            })?;
+            let kid = header.kid.clone().ok_or_else(|| {
+                SecurityError::AuthenticationFailed("jwt missing kid".to_string())
+            })?;
+
+            // Read the token's claimed (but not yet verified) `iss` to pick
+            // which configured provider's JWKS to use, without verifying the
+            // signature first.
+            let unverified = decode_unverified_claims(token)?;
+            let issuer = unverified
+                .get("iss")
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    SecurityError::AuthenticationFailed("jwt missing iss claim".to_string())
+                })?;
+
+            let provider = self
+                .providers
+                .iter()
+                .find(|p| p.issuer == issuer)
+                .ok_or_else(|| {
+                    SecurityError::AuthenticationFailed(format!("untrusted issuer: {issuer}"))
+                })?;
+
+            let jwk = self.resolve_key(provider, &kid).await?;
+            let (alg, decoding_key) = decoding_key_for(&jwk)?;
+
+            let mut validation = Validation::new(alg);
+            validation.set_issuer(&[&provider.issuer]);
+            validation.set_audience(&[&provider.audience]);
+
+            let data = decode::<Value>(token, &decoding_key, &validation).map_err(|err| {
+                SecurityError::AuthenticationFailed(format!("jwt verification failed: {err}"))
+            })?;
+
+            let claims = data.claims;
+            let user_id = claims
+                .get(&provider.user_id_claim)
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    SecurityError::AuthenticationFailed(format!(
+                        "missing '{}' claim",
+                        provider.user_id_claim
+                    ))
+                })?
+                .to_string();
+
+            let roles = match claims.get(&provider.roles_claim) {
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+                Some(Value::String(single)) => vec![single.clone()],
+                _ => Vec::new(),
+            };
+
+            Ok(UserContext { user_id, roles })
+        }
+
+        /// Find the signing key for `kid`, fetching (or re-fetching) the
+        /// issuer's JWKS if it's missing or stale.
+        async fn resolve_key(
+            &self,
+            provider: &OidcProviderConfig,
+            kid: &str,
+        ) -> Result<jsonwebtoken::jwk::Jwk, SecurityError> {
+            let ttl = Duration::from_secs(provider.jwks_ttl_secs);
+
+            {
+                let cache = self.jwks_cache.read().await;
+                if let Some(cached) = cache.get(&provider.issuer) {
+                    if cached.fetched_at.elapsed() < ttl {
+                        if let Some(jwk) = find_kid(&cached.jwks, kid) {
+                            return Ok(jwk);
+                        }
+                    }
+                }
+            }
+
+            // Cache miss or rotation: refresh from the provider.
+            let jwks = self.fetch_jwks(provider).await?;
+            let found = find_kid(&jwks, kid);
+
+            let mut cache = self.jwks_cache.write().await;
+            cache.insert(
+                provider.issuer.clone(),
+                CachedJwks {
+                    jwks,
+                    fetched_at: Instant::now(),
+                },
+            );
+
+            found.ok_or_else(|| {
+                SecurityError::AuthenticationFailed(format!("unknown signing key: {kid}"))
+            })
+        }
+
+        async fn fetch_jwks(&self, provider: &OidcProviderConfig) -> Result<JwkSet, SecurityError> {
+            self.http
+                .get(&provider.jwks_uri)
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+                .map_err(|err| {
+                    SecurityError::AuthenticationFailed(format!("jwks fetch failed: {err}"))
+                })?
+                .json::<JwkSet>()
+                .await
+                .map_err(|err| {
+                    SecurityError::AuthenticationFailed(format!("jwks decode failed: {err}"))
+                })
+        }
+    }
+
+    fn find_kid(jwks: &JwkSet, kid: &str) -> Option<jsonwebtoken::jwk::Jwk> {
+        jwks.keys
+            .iter()
+            .find(|key| key.common.key_id.as_deref() == Some(kid))
+            .cloned()
+    }
+
+    fn decoding_key_for(
+        jwk: &jsonwebtoken::jwk::Jwk,
+    ) -> Result<(Algorithm, DecodingKey), SecurityError> {
+        match &jwk.algorithm {
+            AlgorithmParameters::RSA(rsa) => {
+                let key = DecodingKey::from_rsa_components(&rsa.n, &rsa.e).map_err(|err| {
+                    SecurityError::AuthenticationFailed(format!("invalid rsa jwk: {err}"))
+                })?;
+                Ok((Algorithm::RS256, key))
+            }
+            AlgorithmParameters::EllipticCurve(ec) => {
+                let key = DecodingKey::from_ec_components(&ec.x, &ec.y).map_err(|err| {
+                    SecurityError::AuthenticationFailed(format!("invalid ec jwk: {err}"))
+                })?;
+                Ok((Algorithm::ES256, key))
+            }
+            _ => Err(SecurityError::AuthenticationFailed(
+                "unsupported jwk key type".to_string(),
+            )),
+        }
+    }
+
+    /// Decode the JWT payload without verifying its signature, solely to
+    /// read the `iss` claim and pick which provider to verify against.
+    fn decode_unverified_claims(token: &str) -> Result<Value, SecurityError> {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use base64::Engine;
+
+        let payload = token
+            .split('.')
+            .nth(1)
+            .ok_or_else(|| SecurityError::AuthenticationFailed("malformed jwt".to_string()))?;
+        let bytes = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|err| SecurityError::AuthenticationFailed(format!("malformed jwt: {err}")))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|err| SecurityError::AuthenticationFailed(format!("malformed jwt claims: {err}")))
+    }
+}
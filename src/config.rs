@@ -0,0 +1,445 @@
+//! # Configuration types for SecureAPIs
+//!
+//! Every subsystem is configured through a small, independently deserializable
+//! struct nested under [`SecurityConfig`], so operators can enable/tune one
+//! pipeline stage (e.g. rate limiting) without touching the others.
+
+use serde::{Deserialize, Serialize};
+
+/// Top-level configuration for a [`crate::core::SecurityLayer`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecurityConfig {
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub threat_detection: ThreatDetectionConfig,
+    #[serde(default)]
+    pub replay_protection: ReplayProtectionConfig,
+    #[serde(default)]
+    pub trusted_proxies: TrustedProxyConfig,
+    #[serde(default)]
+    pub ip_reputation: IpReputationConfig,
+    /// CORS policy, built into a `crate::cors::CorsEnforcer` at startup.
+    /// `None` disables CORS handling entirely.
+    #[serde(default)]
+    pub cors: Option<CorsConfig>,
+    #[serde(default)]
+    pub advanced_validation: AdvancedValidationConfig,
+    #[serde(default)]
+    pub headers: HeadersConfig,
+    #[serde(default)]
+    pub request_constraints: RequestConstraintsConfig,
+}
+
+/// Configuration for [`crate::request_constraints::RequestConstraints`], the
+/// slow-request / slow-loris timeout guard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestConstraintsConfig {
+    pub enabled: bool,
+    /// Maximum wall-clock time to receive a full request end-to-end.
+    #[serde(default = "RequestConstraintsConfig::default_slow_request_timeout_secs")]
+    pub slow_request_timeout_secs: u64,
+    /// Budget for receiving just the headers. Not enforced by this crate
+    /// directly (by the time a `Request` reaches it, headers are already
+    /// parsed) - exposed for a transport-level integration to apply.
+    #[serde(default = "RequestConstraintsConfig::default_header_read_timeout_secs")]
+    pub header_read_timeout_secs: u64,
+    /// Budget for receiving the body once headers are in. Enforced by
+    /// `crate::integrations::axum` around the body-buffering read.
+    #[serde(default = "RequestConstraintsConfig::default_body_read_timeout_secs")]
+    pub body_read_timeout_secs: u64,
+}
+
+impl Default for RequestConstraintsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            slow_request_timeout_secs: Self::default_slow_request_timeout_secs(),
+            header_read_timeout_secs: Self::default_header_read_timeout_secs(),
+            body_read_timeout_secs: Self::default_body_read_timeout_secs(),
+        }
+    }
+}
+
+impl RequestConstraintsConfig {
+    fn default_slow_request_timeout_secs() -> u64 {
+        30
+    }
+
+    fn default_header_read_timeout_secs() -> u64 {
+        10
+    }
+
+    fn default_body_read_timeout_secs() -> u64 {
+        20
+    }
+}
+
+/// Configuration for [`crate::headers::SecurityHeaders`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HeadersConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub hsts: HstsConfig,
+    /// Directive -> allowlist, e.g. `"geolocation" -> "()"`, rendered as a
+    /// `Permissions-Policy` header (`geolocation=(), camera=()`, ...).
+    #[serde(default)]
+    pub permissions_policy: std::collections::HashMap<String, String>,
+}
+
+/// Configuration for the `Strict-Transport-Security` header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HstsConfig {
+    pub enabled: bool,
+    #[serde(default = "HstsConfig::default_max_age_secs")]
+    pub max_age_secs: u64,
+    #[serde(default)]
+    pub include_subdomains: bool,
+    #[serde(default)]
+    pub preload: bool,
+}
+
+impl Default for HstsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_age_secs: Self::default_max_age_secs(),
+            include_subdomains: false,
+            preload: false,
+        }
+    }
+}
+
+impl HstsConfig {
+    fn default_max_age_secs() -> u64 {
+        31_536_000 // one year, the conventional HSTS baseline
+    }
+}
+
+/// Configuration for [`crate::advanced_validation::AdvancedValidator`]'s
+/// bounded `Content-Encoding` decompression.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvancedValidationConfig {
+    pub enabled: bool,
+    /// Hard cap on decompressed body size, independent of the ratio below -
+    /// stops a merely-moderate-ratio bomb that's still too large to inspect.
+    #[serde(default = "AdvancedValidationConfig::default_max_decompressed_bytes")]
+    pub max_decompressed_bytes: usize,
+    /// Hard cap on decompressed/compressed size ratio - stops a zip bomb that
+    /// stays under `max_decompressed_bytes` but still inflates absurdly.
+    #[serde(default = "AdvancedValidationConfig::default_max_compression_ratio")]
+    pub max_compression_ratio: u32,
+}
+
+impl Default for AdvancedValidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_decompressed_bytes: Self::default_max_decompressed_bytes(),
+            max_compression_ratio: Self::default_max_compression_ratio(),
+        }
+    }
+}
+
+impl AdvancedValidationConfig {
+    fn default_max_decompressed_bytes() -> usize {
+        10 * 1024 * 1024
+    }
+
+    fn default_max_compression_ratio() -> u32 {
+        100
+    }
+}
+
+/// Declarative configuration for [`crate::cors::CorsEnforcer`]. Equivalent
+/// to building one by hand with `CorsEnforcer::builder()`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CorsConfig {
+    pub allow_origins: Vec<String>,
+    #[serde(default)]
+    pub allow_methods: Vec<String>,
+    #[serde(default)]
+    pub allow_headers: Vec<String>,
+    #[serde(default)]
+    pub expose_headers: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+    #[serde(default)]
+    pub max_age: Option<u64>,
+}
+
+/// Configuration for [`crate::rate_limit::RateLimiter`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Maximum requests allowed per key within `window_secs`.
+    pub max_requests: u32,
+    /// Length of the rate-limit window, in seconds.
+    pub window_secs: u64,
+    /// When set, counts are shared across instances via Redis instead of
+    /// being tracked purely in-process.
+    #[serde(default)]
+    pub redis: Option<RedisRateLimitConfig>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_requests: 100,
+            window_secs: 60,
+            redis: None,
+        }
+    }
+}
+
+/// Configuration for the distributed, Redis-backed rate limiter backend.
+///
+/// The limiter keeps a local allowance refilled from the last-known global
+/// count, and batches deltas back to Redis on a timer or, inline on the hot
+/// path, once a key reaches `flush_every_hits` (see `crate::rate_limit`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisRateLimitConfig {
+    /// `redis://` connection URL.
+    pub url: String,
+    /// How often (in milliseconds) to flush the batched local delta to Redis.
+    #[serde(default = "RedisRateLimitConfig::default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Flush early if a key accumulates this many local hits before the timer fires.
+    #[serde(default = "RedisRateLimitConfig::default_flush_every_hits")]
+    pub flush_every_hits: u32,
+    /// If Redis is unreachable: `true` allows the request through (and logs an
+    /// `InternalError`), `false` rejects it with `RateLimitExceeded`.
+    #[serde(default)]
+    pub fail_open: bool,
+}
+
+impl RedisRateLimitConfig {
+    fn default_flush_interval_ms() -> u64 {
+        250
+    }
+
+    fn default_flush_every_hits() -> u32 {
+        20
+    }
+}
+
+/// Configuration for [`crate::validation::InputValidator`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ValidationConfig {
+    pub enabled: bool,
+}
+
+/// Configuration for [`crate::auth::AuthManager`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AuthConfig {
+    pub enabled: bool,
+    /// If `true`, a request with no resolvable identity is rejected instead
+    /// of passed through as anonymous.
+    pub require_auth: bool,
+    /// Trusted OIDC/OAuth2 issuers accepted for bearer-token verification.
+    /// When empty, only the internal bearer scheme is accepted.
+    #[serde(default)]
+    pub oidc_providers: Vec<OidcProviderConfig>,
+}
+
+/// A trusted OIDC/OAuth2 issuer and how to map its claims onto a
+/// [`crate::auth::UserContext`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcProviderConfig {
+    /// Expected `iss` claim.
+    pub issuer: String,
+    /// JWKS endpoint to fetch signing keys from.
+    pub jwks_uri: String,
+    /// Expected `aud` claim.
+    pub audience: String,
+    /// Claim to map onto `UserContext::user_id` (usually `sub`).
+    #[serde(default = "OidcProviderConfig::default_user_id_claim")]
+    pub user_id_claim: String,
+    /// Claim (string or array of strings) to map onto `UserContext::roles`.
+    #[serde(default = "OidcProviderConfig::default_roles_claim")]
+    pub roles_claim: String,
+    /// How long a fetched JWKS is trusted before being treated as stale.
+    #[serde(default = "OidcProviderConfig::default_jwks_ttl_secs")]
+    pub jwks_ttl_secs: u64,
+}
+
+impl OidcProviderConfig {
+    fn default_user_id_claim() -> String {
+        "sub".to_string()
+    }
+
+    fn default_roles_claim() -> String {
+        "roles".to_string()
+    }
+
+    fn default_jwks_ttl_secs() -> u64 {
+        3600
+    }
+}
+
+/// Configuration for [`crate::monitoring::Monitor`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MonitoringConfig {
+    pub enabled: bool,
+}
+
+/// Configuration for [`crate::replay_protection::ReplayProtection`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ReplayProtectionConfig {
+    /// Opt-in: the stage is skipped entirely unless this is `true`.
+    pub enabled: bool,
+    /// Acceptable clock skew, in seconds, for `X-Timestamp`.
+    #[serde(default = "ReplayProtectionConfig::default_skew_secs")]
+    pub skew_secs: u64,
+    /// Shared HMAC secret per API key (`X-Api-Key` header value).
+    #[serde(default)]
+    pub secrets: std::collections::HashMap<String, String>,
+    /// Threat score added when a replay or forged signature is caught.
+    #[serde(default = "ReplayProtectionConfig::default_score_on_violation")]
+    pub score_on_violation: u32,
+}
+
+impl ReplayProtectionConfig {
+    fn default_skew_secs() -> u64 {
+        300
+    }
+
+    fn default_score_on_violation() -> u32 {
+        60
+    }
+}
+
+/// CIDR allowlist of reverse proxies trusted to set `X-Forwarded-For`/
+/// `X-Real-IP`. Left empty, no proxy is trusted and the socket peer address
+/// is always used as the client IP.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TrustedProxyConfig {
+    /// IPv4/IPv6 CIDRs, e.g. `10.0.0.0/8` or `fd00::/8`.
+    pub trusted_proxies: Vec<String>,
+}
+
+/// Configuration for [`crate::ip_reputation::IpReputation`], the DNSBL-based
+/// VPN/proxy/Tor detector.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IpReputationConfig {
+    pub enabled: bool,
+    /// Blocklist zones to query, most specific first. The first zone with a
+    /// hit wins.
+    #[serde(default)]
+    pub zones: Vec<DnsblZoneConfig>,
+    /// How long both positive and negative lookups are cached.
+    #[serde(default = "IpReputationConfig::default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Per-lookup resolver timeout; a timeout fails open (request is allowed
+    /// through, unscored) rather than blocking the pipeline on a flaky resolver.
+    #[serde(default = "IpReputationConfig::default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl IpReputationConfig {
+    fn default_cache_ttl_secs() -> u64 {
+        3600
+    }
+
+    fn default_timeout_ms() -> u64 {
+        500
+    }
+}
+
+/// A single DNSBL zone and the score each category of hit contributes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsblZoneConfig {
+    /// e.g. `dnsbl.example.org`; queried as `<reversed-ip>.<zone>`.
+    pub zone: String,
+    pub proxy_score: u32,
+    pub vpn_score: u32,
+    pub tor_score: u32,
+}
+
+/// Configuration for the threat-detection rule engine
+/// (see [`crate::threats::RuleEngine`]).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ThreatDetectionConfig {
+    pub enabled: bool,
+    pub block_suspicious: bool,
+    /// Declarative ruleset. Empty means "use the built-in default rules".
+    #[serde(default)]
+    pub rules: Vec<ThreatRuleConfig>,
+    /// Score, and fire the obvious-attack-combo path, without ever
+    /// returning `ThreatDetected` — lets operators vet a ruleset against
+    /// live traffic before enforcing it.
+    #[serde(default)]
+    pub monitor_only: bool,
+    /// Block once a single category accumulates at least this score (the
+    /// "obvious attack combo" path).
+    #[serde(default = "ThreatDetectionConfig::default_combo_threshold")]
+    pub combo_threshold: u32,
+    /// Block once the total score across all categories reaches this,
+    /// regardless of whether any single category looks like an obvious combo.
+    #[serde(default = "ThreatDetectionConfig::default_block_threshold")]
+    pub block_threshold: u32,
+}
+
+impl ThreatDetectionConfig {
+    fn default_combo_threshold() -> u32 {
+        40
+    }
+
+    fn default_block_threshold() -> u32 {
+        100
+    }
+}
+
+/// One declaratively-configured rule for the threat-detection engine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatRuleConfig {
+    pub name: String,
+    /// `sql_injection`, `xss`, `path_traversal`, `command_injection`,
+    /// `scanner_tool`, or `other`.
+    pub category: String,
+    pub field: RuleFieldConfig,
+    pub matcher: RuleMatcherConfig,
+    pub score: u32,
+    /// `low`, `medium`, `high`, or `critical`.
+    pub severity: String,
+}
+
+/// Which part of the request a rule inspects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleFieldConfig {
+    Uri,
+    Body,
+    /// `name: None` matches every header value; `Some(name)` matches one.
+    Header { name: Option<String> },
+}
+
+/// How a rule matches against its field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleMatcherConfig {
+    Substring {
+        needle: String,
+        #[serde(default)]
+        case_insensitive: bool,
+    },
+    /// Matches only when *every* needle is present in the same value (AND
+    /// semantics), unlike `Substring`'s single needle. For signatures like
+    /// `union` + `select` where either needle alone is too common to score
+    /// on its own, but the combination is a meaningful signal.
+    AllSubstrings {
+        needles: Vec<String>,
+        #[serde(default)]
+        case_insensitive: bool,
+    },
+    Regex {
+        pattern: String,
+    },
+}
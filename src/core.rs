@@ -1,9 +1,11 @@
+use crate::audit::{AuditEvent, AuditSink};
 use crate::config::SecurityConfig;
 use crate::ui::state::UIState;
 use http::Request;
 use std::fmt;
 use std::sync::Arc;
 use chrono::Utc;
+use serde::Serialize;
 
 /// Result type for security operations
 pub type SecurityResult<T> = Result<T, SecurityError>;
@@ -83,8 +85,55 @@ impl fmt::Display for SecurityError {
 
 impl std::error::Error for SecurityError {}
 
+impl SecurityError {
+    /// The bare variant name, used for structured logging/audit events
+    /// where the full `Display` message is too verbose.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Self::RateLimitExceeded { .. } => "RateLimitExceeded",
+            Self::AuthenticationFailed(_) => "AuthenticationFailed",
+            Self::AuthorizationFailed(_) => "AuthorizationFailed",
+            Self::InvalidInput { .. } => "InvalidInput",
+            Self::ThreatDetected { .. } => "ThreatDetected",
+            Self::ConfigError(_) => "ConfigError",
+            Self::InternalError(_) => "InternalError",
+            Self::CorsViolation(_) => "CorsViolation",
+            Self::CsrfViolation(_) => "CsrfViolation",
+            Self::HttpsRequired => "HttpsRequired",
+            Self::TransportLayerViolation(_) => "TransportLayerViolation",
+            Self::IpBlocked(_) => "IpBlocked",
+            Self::VpnDetected(_) => "VpnDetected",
+            Self::ProxyDetected(_) => "ProxyDetected",
+            Self::RequestTimeout(_) => "RequestTimeout",
+            Self::ConnectionTimeout(_) => "ConnectionTimeout",
+            Self::ReplayDetected(_) => "ReplayDetected",
+        }
+    }
+
+    /// The severity associated with this error, for audit/monitoring.
+    pub fn severity(&self) -> ThreatSeverity {
+        match self {
+            Self::ThreatDetected { severity, .. } => *severity,
+            Self::VpnDetected(_) | Self::ProxyDetected(_) | Self::ReplayDetected(_) => {
+                ThreatSeverity::High
+            }
+            Self::RateLimitExceeded { .. }
+            | Self::AuthenticationFailed(_)
+            | Self::AuthorizationFailed(_)
+            | Self::InvalidInput { .. }
+            | Self::RequestTimeout(_)
+            | Self::ConnectionTimeout(_) => ThreatSeverity::Medium,
+            Self::CorsViolation(_) | Self::CsrfViolation(_) | Self::IpBlocked(_) => {
+                ThreatSeverity::Medium
+            }
+            Self::HttpsRequired | Self::TransportLayerViolation(_) => ThreatSeverity::Low,
+            Self::ConfigError(_) | Self::InternalError(_) => ThreatSeverity::Low,
+        }
+    }
+}
+
 /// Threat severity levels
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum ThreatSeverity {
     Low,
     Medium,
@@ -102,6 +151,11 @@ pub struct SecurityContext {
     pub roles: Vec<String>,
     pub threat_score: u32,
     pub metadata: std::collections::HashMap<String, String>,
+    /// The request body after `crate::advanced_validation::AdvancedValidator`
+    /// has undone any `Content-Encoding`, so the rule engine scans the real
+    /// payload rather than its compressed bytes. `None` if advanced
+    /// validation is disabled or the decoded body wasn't valid UTF-8.
+    pub decoded_body: Option<String>,
 }
 
 impl SecurityContext {
@@ -114,6 +168,7 @@ impl SecurityContext {
             roles: Vec::new(),
             threat_score: 0,
             metadata: std::collections::HashMap::new(),
+            decoded_body: None,
         }
     }
 
@@ -140,16 +195,41 @@ pub struct SecurityLayer {
     config: Arc<SecurityConfig>,
     rate_limiter: Arc<crate::rate_limit::RateLimiter>,
     validator: Arc<crate::validation::InputValidator>,
+    advanced_validator: Arc<crate::advanced_validation::AdvancedValidator>,
     auth_manager: Arc<crate::auth::AuthManager>,
+    replay_protection: Arc<crate::replay_protection::ReplayProtection>,
+    ip_reputation: Arc<crate::ip_reputation::IpReputation>,
+    rule_engine: Arc<crate::threats::RuleEngine>,
     monitor: Arc<crate::monitoring::Monitor>,
     ui_state: Option<Arc<UIState>>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    trusted_proxies: Vec<ipnetwork::IpNetwork>,
+    cors: Option<Arc<crate::cors::CorsEnforcer>>,
+    headers: Arc<crate::headers::SecurityHeaders>,
+    request_constraints: Arc<crate::request_constraints::RequestConstraints>,
 }
 
 impl SecurityLayer {
     /// Create a new security layer with the given configuration
     pub fn new(config: SecurityConfig) -> Self {
+        let trusted_proxies = config
+            .trusted_proxies
+            .trusted_proxies
+            .iter()
+            .filter_map(|cidr| match cidr.parse::<ipnetwork::IpNetwork>() {
+                Ok(net) => Some(net),
+                Err(err) => {
+                    tracing::warn!(cidr = %cidr, error = %err, "ignoring invalid trusted proxy CIDR");
+                    None
+                }
+            })
+            .collect();
+        let cors = config
+            .cors
+            .as_ref()
+            .map(|cors_config| Arc::new(crate::cors::CorsEnforcer::from_config(cors_config)));
         let config = Arc::new(config);
-        
+
         Self {
             rate_limiter: Arc::new(crate::rate_limit::RateLimiter::new(
                 config.rate_limit.clone(),
@@ -157,27 +237,111 @@ impl SecurityLayer {
             validator: Arc::new(crate::validation::InputValidator::new(
                 config.validation.clone(),
             )),
+            advanced_validator: Arc::new(crate::advanced_validation::AdvancedValidator::new(
+                &config.advanced_validation,
+            )),
             auth_manager: Arc::new(crate::auth::AuthManager::new(config.auth.clone())),
+            replay_protection: Arc::new(crate::replay_protection::ReplayProtection::new(
+                config.replay_protection.clone(),
+            )),
+            ip_reputation: Arc::new(crate::ip_reputation::IpReputation::new(
+                config.ip_reputation.clone(),
+            )),
+            rule_engine: Arc::new(crate::threats::RuleEngine::new(&config.threat_detection)),
             monitor: Arc::new(crate::monitoring::Monitor::new(config.monitoring.clone())),
+            headers: Arc::new(crate::headers::SecurityHeaders::new(config.headers.clone())),
+            request_constraints: Arc::new(crate::request_constraints::RequestConstraints::new(
+                config.request_constraints.clone(),
+            )),
             config,
             ui_state: None,
+            audit_sink: None,
+            trusted_proxies,
+            cors,
         }
     }
 
+    /// Attach a CORS policy built by hand, overriding any `config.cors`.
+    pub fn with_cors(mut self, cors: Arc<crate::cors::CorsEnforcer>) -> Self {
+        self.cors = Some(cors);
+        self
+    }
+
+    /// Evaluate the configured CORS policy against `request`, if any is set.
+    pub fn evaluate_cors<B>(&self, request: &Request<B>) -> Option<crate::cors::CorsOutcome> {
+        self.cors.as_ref().map(|cors| cors.evaluate(request))
+    }
+
+    /// Baseline response headers (HSTS, Permissions-Policy, etc.) for
+    /// `request`, honoring WebSocket-upgrade suppression. Empty if headers
+    /// emission is disabled.
+    pub fn response_headers<B>(&self, request: &Request<B>) -> std::collections::HashMap<String, String> {
+        self.headers.headers_for(request)
+    }
+
+    /// The configured slow-request timeout, or `None` if the guard is
+    /// disabled. Callers with an event loop (the axum middleware) wrap their
+    /// own read/handler futures in `tokio::time::timeout` with this; the
+    /// FFI path instead passes a caller-supplied elapsed-time hint to
+    /// [`Self::check_elapsed`].
+    pub fn slow_request_timeout(&self) -> Option<std::time::Duration> {
+        self.request_constraints
+            .enabled()
+            .then(|| self.request_constraints.slow_request_timeout())
+    }
+
+    /// The configured body-read timeout, or `None` if the guard is disabled.
+    /// Narrower than [`Self::slow_request_timeout`]: it bounds just the body
+    /// read, not the downstream handler too.
+    pub fn body_read_timeout(&self) -> Option<std::time::Duration> {
+        self.request_constraints
+            .enabled()
+            .then(|| self.request_constraints.body_read_timeout())
+    }
+
+    /// Check a caller-supplied elapsed-time hint against
+    /// `slow_request_timeout`, for callers with no event loop of their own
+    /// (the synchronous FFI path) or that want the check ahead of driving
+    /// several requests through their own shared runtime (the batch FFI
+    /// path).
+    pub fn check_elapsed(&self, elapsed: std::time::Duration) -> SecurityResult<()> {
+        self.request_constraints.check_elapsed(elapsed)
+    }
+
     /// Set the UI state for metrics collection
     pub fn with_ui_state(mut self, ui_state: Arc<UIState>) -> Self {
         self.ui_state = Some(ui_state);
         self
     }
 
-    /// Process an incoming request through the security pipeline
-    pub async fn process_request<B>(
+    /// Attach an audit sink that receives every allow/block decision.
+    pub fn with_audit_sink(mut self, audit_sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
+
+    /// Emit an audit event if a sink is attached. Fire-and-forget: sinks are
+    /// responsible for never letting a slow backend stall this call.
+    async fn audit(&self, event: AuditEvent) {
+        if let Some(sink) = &self.audit_sink {
+            sink.emit(event).await;
+        }
+    }
+
+    /// Process an incoming request through the security pipeline.
+    ///
+    /// `peer_addr` is the socket peer address of the immediate connection
+    /// (i.e. what the TCP/TLS layer handed us, before any `X-Forwarded-For`
+    /// is considered) — it's required so `extract_client_ip` can tell
+    /// whether that peer is a trusted proxy before trusting anything it forwarded.
+    pub async fn process_request<B: AsRef<[u8]>>(
         &self,
         request: &Request<B>,
+        peer_addr: std::net::IpAddr,
     ) -> SecurityResult<SecurityContext>
     {
         // Extract client IP
-        let client_ip = self.extract_client_ip(request);
+        let client_ip = self.extract_client_ip(request, peer_addr);
         let request_id = uuid::Uuid::new_v4().to_string();
         let timestamp = Utc::now();
         let method = request.method().to_string();
@@ -197,7 +361,7 @@ impl SecurityLayer {
 
         // 1. Rate limiting check (fastest check first)
         if self.config.rate_limit.enabled {
-            if let Err(_) = self.rate_limiter.check(&client_ip).await {
+            if let Err(rejection) = self.rate_limiter.check(&client_ip).await {
                 // Rate limited
                 if let Some(ui_state) = &self.ui_state {
                     ui_state.rate_limited.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -219,11 +383,42 @@ impl SecurityLayer {
                     };
                     ui_state.add_request_log(log).await;
                 }
-                return Err(SecurityError::RateLimitExceeded { retry_after: 60 });
+                let error = SecurityError::RateLimitExceeded {
+                    retry_after: rejection.retry_after,
+                };
+                self.audit(AuditEvent::blocked(&context, &error, timestamp)).await;
+                return Err(error);
+            }
+        }
+
+        // 2. Replay protection (opt-in HMAC-signed requests)
+        if self.config.replay_protection.enabled {
+            if let Err(error) = self.replay_protection.validate(request, &mut context).await {
+                if let Some(ui_state) = &self.ui_state {
+                    ui_state.blocked_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    let log = crate::ui::state::RequestLog {
+                        id: request_id.clone(),
+                        timestamp,
+                        method: method.clone(),
+                        path: path.clone(),
+                        client_ip: client_ip.clone(),
+                        user_agent: user_agent.clone(),
+                        user_id: None,
+                        status_code: 409,
+                        response_time_ms: 0.0,
+                        threat_score: context.threat_score as f64,
+                        blocked: true,
+                        reason: Some("Replay detected".to_string()),
+                        headers: std::collections::HashMap::new(),
+                    };
+                    ui_state.add_request_log(log).await;
+                }
+                self.audit(AuditEvent::blocked(&context, &error, timestamp)).await;
+                return Err(error);
             }
         }
 
-        // 2. Authentication check
+        // 3. Authentication check
         if self.config.auth.enabled {
             if let Some(user_context) = self.auth_manager.authenticate(request).await? {
                 context = context.with_user(user_context.user_id, user_context.roles);
@@ -249,13 +444,15 @@ impl SecurityLayer {
                     };
                     ui_state.add_request_log(log).await;
                 }
-                return Err(SecurityError::AuthenticationFailed(
+                let error = SecurityError::AuthenticationFailed(
                     "Authentication required".to_string(),
-                ));
+                );
+                self.audit(AuditEvent::blocked(&context, &error, timestamp)).await;
+                return Err(error);
             }
         }
 
-        // 3. Input validation
+        // 4. Input validation
         if self.config.validation.enabled {
             if let Err(_) = self.validator.validate_request(request, &mut context).await {
                 // Validation failed
@@ -279,16 +476,47 @@ impl SecurityLayer {
                     };
                     ui_state.add_request_log(log).await;
                 }
-                return Err(SecurityError::InvalidInput {
+                let error = SecurityError::InvalidInput {
                     reason: "Input validation failed".to_string(),
                     field: None,
-                });
+                };
+                self.audit(AuditEvent::blocked(&context, &error, timestamp)).await;
+                return Err(error);
             }
         }
 
-        // 4. Threat detection
+        // 4b. Advanced validation: bounded Content-Encoding decompression so
+        // threat detection below scans the real payload, not compressed bytes.
+        if self.config.advanced_validation.enabled {
+            if let Err(error) = self.advanced_validator.validate(request, &mut context).await {
+                if let Some(ui_state) = &self.ui_state {
+                    ui_state.validation_failures.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    ui_state.blocked_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    let log = crate::ui::state::RequestLog {
+                        id: request_id.clone(),
+                        timestamp,
+                        method: method.clone(),
+                        path: path.clone(),
+                        client_ip: client_ip.clone(),
+                        user_agent: user_agent.clone(),
+                        user_id: context.user_id.clone(),
+                        status_code: 400,
+                        response_time_ms: 0.0,
+                        threat_score: context.threat_score as f64,
+                        blocked: true,
+                        reason: Some(error.to_string()),
+                        headers: std::collections::HashMap::new(),
+                    };
+                    ui_state.add_request_log(log).await;
+                }
+                self.audit(AuditEvent::blocked(&context, &error, timestamp)).await;
+                return Err(error);
+            }
+        }
+
+        // 5. Threat detection
         if self.config.threat_detection.enabled {
-            if let Err(_) = self.detect_threats(request, &mut context).await {
+            if let Err(error) = self.detect_threats(request, &mut context).await {
                 // Threat detected - always log as blocked threat
                 if let Some(ui_state) = &self.ui_state {
                     ui_state.blocked_requests.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -304,19 +532,17 @@ impl SecurityLayer {
                         response_time_ms: 0.0,
                         threat_score: context.threat_score as f64,
                         blocked: true,
-                        reason: Some("Threat detected".to_string()),
+                        reason: Some(error.to_string()),
                         headers: std::collections::HashMap::new(),
                     };
                     ui_state.add_request_log(log).await;
                 }
-                return Err(SecurityError::ThreatDetected {
-                    threat_type: "Suspicious request pattern".to_string(),
-                    severity: ThreatSeverity::High,
-                });
+                self.audit(AuditEvent::blocked(&context, &error, timestamp)).await;
+                return Err(error);
             }
         }
 
-        // 5. Monitoring
+        // 6. Monitoring
         if self.config.monitoring.enabled {
             self.monitor.log_request(request, &context).await;
         }
@@ -341,34 +567,78 @@ impl SecurityLayer {
             ui_state.add_request_log(log).await;
         }
 
+        self.audit(AuditEvent::allowed(&context, timestamp)).await;
+
         Ok(context)
     }
 
     /// Synchronous version of process_request for FFI bindings
-    pub fn process_request_sync<B>(
+    ///
+    /// `elapsed_hint`, when given, is how long the caller (e.g. a gateway in
+    /// front of this binding) already spent receiving the request - there's
+    /// no event loop here to enforce a timeout on that wait directly, so it's
+    /// checked against `slow_request_timeout` up front instead.
+    pub fn process_request_sync<B: AsRef<[u8]>>(
         &self,
         request: &Request<B>,
+        peer_addr: std::net::IpAddr,
+        elapsed_hint: Option<std::time::Duration>,
     ) -> SecurityResult<SecurityContext> {
+        if let Some(elapsed) = elapsed_hint {
+            self.check_elapsed(elapsed)?;
+        }
+
         // Create a runtime for executing async code synchronously
         let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(self.process_request(request))
+        rt.block_on(self.process_request(request, peer_addr))
     }
 
-    fn extract_client_ip<B>(&self, request: &Request<B>) -> String {
-        // Try X-Forwarded-For, X-Real-IP, or connection IP
-        request
+    /// Resolve the real client IP.
+    ///
+    /// If `peer_addr` (the actual socket peer) isn't a trusted proxy,
+    /// forwarding headers are ignored entirely — trusting them would let any
+    /// client spoof its IP. Otherwise, `X-Forwarded-For` is walked
+    /// right-to-left (the order hops are appended in), skipping entries that
+    /// are themselves trusted proxies, and the first non-trusted address is
+    /// the real client. `X-Real-IP` is used as a single-hop fallback.
+    fn extract_client_ip<B>(&self, request: &Request<B>, peer_addr: std::net::IpAddr) -> String {
+        if !self.is_trusted_proxy(peer_addr) {
+            return peer_addr.to_string();
+        }
+
+        let chain = self.forwarded_chain(request);
+        for candidate in chain.iter().rev() {
+            match candidate.parse::<std::net::IpAddr>() {
+                Ok(ip) if !self.is_trusted_proxy(ip) => return ip.to_string(),
+                Ok(_trusted_hop) => continue,
+                // An unparseable entry means we can no longer trust anything
+                // further left in the chain either.
+                Err(_) => break,
+            }
+        }
+
+        peer_addr.to_string()
+    }
+
+    fn is_trusted_proxy(&self, ip: std::net::IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(ip))
+    }
+
+    fn forwarded_chain<B>(&self, request: &Request<B>) -> Vec<String> {
+        if let Some(xff) = request
             .headers()
             .get("x-forwarded-for")
             .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.split(',').next())
-            .or_else(|| {
-                request
-                    .headers()
-                    .get("x-real-ip")
-                    .and_then(|h| h.to_str().ok())
-            })
-            .unwrap_or("unknown")
-            .to_string()
+        {
+            return xff.split(',').map(|s| s.trim().to_string()).collect();
+        }
+
+        request
+            .headers()
+            .get("x-real-ip")
+            .and_then(|h| h.to_str().ok())
+            .map(|s| vec![s.trim().to_string()])
+            .unwrap_or_default()
     }
 
     async fn detect_threats<B>(
@@ -376,108 +646,48 @@ impl SecurityLayer {
         request: &Request<B>,
         context: &mut SecurityContext,
     ) -> SecurityResult<()> {
-        // Smart threat detection based on request patterns
-        let uri = request.uri().to_string();
-        let uri_lower = uri.to_lowercase();
-        
-        // Track different attack indicators
-        let mut has_sql_combo = false;
-        let mut has_xss = false;
-        let mut has_path_traversal = false;
-        
-        // IMPORTANT: Only flag clear, unambiguous attack patterns
-        // Threshold is 100 to avoid false positives on legitimate URLs
-        
-        // Path traversal attempts - only if obvious
-        if uri.contains("../") || uri.contains("..\\") {
-            // Check if it looks like a real attack (multiple traversals)
-            if uri.contains("../../../") || uri.contains("..\\..\\..\\") {
-                context.add_threat_score(40);
-                has_path_traversal = true;
-            }
-        }
-        
-        // URL-encoded path traversal
-        if uri.contains("..%2f") || uri.contains("..%5c") {
-            context.add_threat_score(50);
-            has_path_traversal = true;
-        }
-        
-        // XSS attempts - very specific patterns
-        if uri_lower.contains("<script") || uri_lower.contains("javascript:alert") {
-            context.add_threat_score(60);
-            has_xss = true;
-        }
-        
-        // Obvious XSS event handlers
-        if (uri_lower.contains("onerror=") && !uri_lower.contains("onerror_")) ||
-           (uri_lower.contains("onload=") && !uri_lower.contains("onload_")) {
-            context.add_threat_score(50);
-            has_xss = true;
-        }
-        
-        // SQL Injection - check for dangerous combos
-        // These keywords together strongly indicate SQL injection
-        if uri_lower.contains("union") && uri_lower.contains("select") {
-            context.add_threat_score(60);
-            has_sql_combo = true;
-        }
-        
-        if uri_lower.contains("'; drop") || uri_lower.contains("'; delete") {
-            context.add_threat_score(60);
-            has_sql_combo = true;
-        }
-        
-        if uri_lower.contains("' or '1'='1") || uri_lower.contains("1'or'1'='1") {
-            context.add_threat_score(60);
-            has_sql_combo = true;
-        }
-        
-        // Command injection - shell metacharacters
-        if (uri.contains("`;") || uri.contains("`|")) && uri.contains("/api/") {
-            context.add_threat_score(50);
-        }
-        
-        // Check headers for suspicious patterns
-        for (header_name, header_value) in request.headers() {
-            if let Ok(value_str) = header_value.to_str() {
-                let value_lower = value_str.to_lowercase();
-                
-                // Very large headers (potential buffer overflow)
-                if value_str.len() > 8192 {
-                    context.add_threat_score(30);
-                }
-                
-                // Suspicious scanning tools
-                if header_name == "user-agent" {
-                    if value_lower.contains("sqlmap") || value_lower.contains("nikto") || 
-                       value_lower.contains("nmap") || value_lower.contains("masscan") ||
-                       value_lower.contains("burp") {
-                        context.add_threat_score(70);
+        // DNSBL-based VPN/proxy/Tor reputation check
+        if self.config.ip_reputation.enabled {
+            if let Ok(client_ip) = context.client_ip.parse::<std::net::IpAddr>() {
+                if let crate::ip_reputation::ReputationVerdict::Listed {
+                    category,
+                    zone,
+                    score,
+                } = self.ip_reputation.check(client_ip).await
+                {
+                    context.add_threat_score(score);
+                    if self.config.threat_detection.block_suspicious {
+                        use crate::ip_reputation::ReputationCategory;
+                        return Err(match category {
+                            ReputationCategory::Vpn => {
+                                SecurityError::VpnDetected(format!("listed in {zone}"))
+                            }
+                            ReputationCategory::Proxy | ReputationCategory::TorExit => {
+                                SecurityError::ProxyDetected(format!("listed in {zone}"))
+                            }
+                        });
                     }
                 }
             }
         }
-        
-        // Block decision logic:
-        if self.config.threat_detection.block_suspicious {
-            // Case 1: Obvious attack combo (even if score is low)
-            if has_sql_combo || has_xss || has_path_traversal {
-                if context.threat_score >= 40 {
-                    return Err(SecurityError::ThreatDetected {
-                        threat_type: "Suspicious request pattern".to_string(),
-                        severity: ThreatSeverity::High,
-                    });
-                }
-            }
-            
-            // Case 2: Very high score (multiple indicators)
-            if context.threat_score >= 100 {
-                return Err(SecurityError::ThreatDetected {
-                    threat_type: "Suspicious request pattern".to_string(),
-                    severity: ThreatSeverity::High,
-                });
-            }
+
+        // Rule-engine based pattern detection (SQLi, XSS, path traversal,
+        // command injection, scanner fingerprints, ...). The ruleset is
+        // configurable; see `crate::threats`.
+        let uri = request.uri().to_string();
+        let target = crate::threats::RuleTarget {
+            uri: &uri,
+            headers: request.headers(),
+            body: context.decoded_body.as_deref(),
+        };
+        let outcome = self.rule_engine.evaluate(&target);
+        context.add_threat_score(outcome.total_score);
+
+        if self.config.threat_detection.block_suspicious && outcome.should_block() {
+            return Err(SecurityError::ThreatDetected {
+                threat_type: outcome.threat_type(),
+                severity: outcome.severity,
+            });
         }
 
         Ok(())
@@ -490,9 +700,18 @@ impl Clone for SecurityLayer {
             config: Arc::clone(&self.config),
             rate_limiter: Arc::clone(&self.rate_limiter),
             validator: Arc::clone(&self.validator),
+            advanced_validator: Arc::clone(&self.advanced_validator),
             auth_manager: Arc::clone(&self.auth_manager),
+            replay_protection: Arc::clone(&self.replay_protection),
+            ip_reputation: Arc::clone(&self.ip_reputation),
+            rule_engine: Arc::clone(&self.rule_engine),
             monitor: Arc::clone(&self.monitor),
             ui_state: self.ui_state.clone(),
+            audit_sink: self.audit_sink.clone(),
+            trusted_proxies: self.trusted_proxies.clone(),
+            cors: self.cors.clone(),
+            headers: Arc::clone(&self.headers),
+            request_constraints: Arc::clone(&self.request_constraints),
         }
     }
 }
@@ -0,0 +1,199 @@
+//! # CORS enforcement
+//!
+//! A complete CORS implementation (preflight handling plus header emission
+//! on the actual request), rather than a simple allow/deny check. Built via
+//! [`CorsEnforcer::builder`], or from a [`crate::config::CorsConfig`] when
+//! driven by `SecurityConfig`.
+
+use crate::config::CorsConfig;
+use http::{Method, Request};
+use std::collections::{HashMap, HashSet};
+
+/// The result of evaluating CORS for one request.
+#[derive(Debug, Clone)]
+pub enum CorsOutcome {
+    /// No `Origin` header: not a CORS request, nothing to do.
+    NotApplicable,
+    /// An `OPTIONS` preflight matched an allowed origin; the caller should
+    /// short-circuit with a 204 carrying these headers instead of forwarding
+    /// to the next handler.
+    Preflight(HashMap<String, String>),
+    /// An actual (non-preflight) request from an allowed origin; the caller
+    /// should apply these headers to the outgoing response.
+    Allowed(HashMap<String, String>),
+    /// The request's `Origin` isn't in `allow_origins`.
+    Rejected,
+}
+
+/// Enforces a CORS policy: preflight handling plus response headers,
+/// equivalent to a `warp`/`tower-http`-style CORS filter.
+pub struct CorsEnforcer {
+    allow_origins: HashSet<String>,
+    allow_methods: Vec<String>,
+    allow_headers: Vec<String>,
+    expose_headers: Vec<String>,
+    allow_credentials: bool,
+    max_age: Option<u64>,
+}
+
+impl CorsEnforcer {
+    pub fn builder() -> CorsEnforcerBuilder {
+        CorsEnforcerBuilder::default()
+    }
+
+    /// Build from a declarative [`CorsConfig`] (e.g. parsed from JSON via the FFI layer).
+    pub fn from_config(config: &CorsConfig) -> Self {
+        CorsEnforcerBuilder::default()
+            .allow_origins(config.allow_origins.clone())
+            .allow_methods(config.allow_methods.clone())
+            .allow_headers(config.allow_headers.clone())
+            .expose_headers(config.expose_headers.clone())
+            .allow_credentials(config.allow_credentials)
+            .max_age(config.max_age)
+            .build()
+    }
+
+    /// Evaluate a request against this policy.
+    pub fn evaluate<B>(&self, request: &Request<B>) -> CorsOutcome {
+        let Some(origin) = request
+            .headers()
+            .get(http::header::ORIGIN)
+            .and_then(|h| h.to_str().ok())
+        else {
+            return CorsOutcome::NotApplicable;
+        };
+
+        if !self.origin_allowed(origin) {
+            return CorsOutcome::Rejected;
+        }
+
+        let is_preflight = request.method() == Method::OPTIONS
+            && request
+                .headers()
+                .contains_key("access-control-request-method");
+
+        if is_preflight {
+            let mut headers = self.origin_headers(origin);
+            headers.insert(
+                "Access-Control-Allow-Methods".to_string(),
+                self.allow_methods.join(", "),
+            );
+
+            let allow_headers = if self.allow_headers.is_empty() {
+                // Mirror back whatever the browser asked to send.
+                request
+                    .headers()
+                    .get("access-control-request-headers")
+                    .and_then(|h| h.to_str().ok())
+                    .unwrap_or("")
+                    .to_string()
+            } else {
+                self.allow_headers.join(", ")
+            };
+            headers.insert("Access-Control-Allow-Headers".to_string(), allow_headers);
+
+            if let Some(max_age) = self.max_age {
+                headers.insert("Access-Control-Max-Age".to_string(), max_age.to_string());
+            }
+
+            return CorsOutcome::Preflight(headers);
+        }
+
+        let mut headers = self.origin_headers(origin);
+        if !self.expose_headers.is_empty() {
+            headers.insert(
+                "Access-Control-Expose-Headers".to_string(),
+                self.expose_headers.join(", "),
+            );
+        }
+        CorsOutcome::Allowed(headers)
+    }
+
+    fn origin_allowed(&self, origin: &str) -> bool {
+        self.allow_origins.contains("*") || self.allow_origins.contains(origin)
+    }
+
+    /// `Access-Control-Allow-Origin` (+ `Vary`/`Allow-Credentials`) shared by
+    /// both the preflight and actual-request responses.
+    ///
+    /// When credentials are allowed, `*` must never be emitted even if
+    /// configured — the spec forbids it, and browsers reject it outright —
+    /// so we always echo the specific request `Origin` in that case.
+    fn origin_headers(&self, origin: &str) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+
+        let wildcard_ok = self.allow_origins.contains("*") && !self.allow_credentials;
+        if wildcard_ok {
+            headers.insert("Access-Control-Allow-Origin".to_string(), "*".to_string());
+        } else {
+            headers.insert(
+                "Access-Control-Allow-Origin".to_string(),
+                origin.to_string(),
+            );
+            headers.insert("Vary".to_string(), "Origin".to_string());
+        }
+
+        if self.allow_credentials {
+            headers.insert(
+                "Access-Control-Allow-Credentials".to_string(),
+                "true".to_string(),
+            );
+        }
+
+        headers
+    }
+}
+
+/// Builder for [`CorsEnforcer`].
+#[derive(Default)]
+pub struct CorsEnforcerBuilder {
+    allow_origins: Vec<String>,
+    allow_methods: Vec<String>,
+    allow_headers: Vec<String>,
+    expose_headers: Vec<String>,
+    allow_credentials: bool,
+    max_age: Option<u64>,
+}
+
+impl CorsEnforcerBuilder {
+    pub fn allow_origins(mut self, origins: impl IntoIterator<Item = String>) -> Self {
+        self.allow_origins = origins.into_iter().collect();
+        self
+    }
+
+    pub fn allow_methods(mut self, methods: impl IntoIterator<Item = String>) -> Self {
+        self.allow_methods = methods.into_iter().collect();
+        self
+    }
+
+    pub fn allow_headers(mut self, headers: impl IntoIterator<Item = String>) -> Self {
+        self.allow_headers = headers.into_iter().collect();
+        self
+    }
+
+    pub fn expose_headers(mut self, headers: impl IntoIterator<Item = String>) -> Self {
+        self.expose_headers = headers.into_iter().collect();
+        self
+    }
+
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    pub fn max_age(mut self, max_age: Option<u64>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    pub fn build(self) -> CorsEnforcer {
+        CorsEnforcer {
+            allow_origins: self.allow_origins.into_iter().collect(),
+            allow_methods: self.allow_methods,
+            allow_headers: self.allow_headers,
+            expose_headers: self.expose_headers,
+            allow_credentials: self.allow_credentials,
+            max_age: self.max_age,
+        }
+    }
+}
@@ -23,6 +23,17 @@ pub struct SecurityCheckResult {
     pub headers_json: *const c_char,
 }
 
+/// Result of [`secureapis_check_requests_batch`]: a heap-allocated array of
+/// `len` [`SecurityCheckResult`]s, in the same order as the input array.
+/// Must be freed with `secureapis_free_result_array`.
+#[repr(C)]
+pub struct SecurityCheckResultArray {
+    /// Pointer to the first of `len` contiguous `SecurityCheckResult`s.
+    pub results: *mut SecurityCheckResult,
+    /// Number of results in `results`.
+    pub len: usize,
+}
+
 /// Free a string allocated by Rust
 #[no_mangle]
 pub extern "C" fn secureapis_free_string(s: *mut c_char) {
@@ -74,6 +85,7 @@ pub extern "C" fn secureapis_check_request(
     headers_json: *const c_char,
     body: *const c_char,
     ip: *const c_char,
+    elapsed_ms: u64,
 ) -> *mut SecurityCheckResult {
     if security_layer.is_null() {
         return ptr::null_mut();
@@ -87,55 +99,247 @@ pub extern "C" fn secureapis_check_request(
     let ip_str = unsafe { CStr::from_ptr(ip) }.to_str().unwrap_or("");
 
     // Create a mock HTTP request for the security layer
-    let request = create_mock_request(method_str, url_str, headers_str, body_str, ip_str);
+    let request = create_mock_request(method_str, url_str, headers_str, body_str);
+
+    // `ip` is the caller's own view of the peer address (the gateway already
+    // terminated the connection), so it's trusted directly rather than
+    // re-derived from a spoofable forwarding header.
+    let peer_addr: std::net::IpAddr = match ip_str.parse() {
+        Ok(ip) => ip,
+        Err(_) => std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+    };
+
+    // How long the caller took to assemble this request before handing it to
+    // us, e.g. time spent reading headers/body off the socket. The FFI path
+    // has no event loop to enforce a `tokio::time::timeout` with, so the
+    // slow-request check takes this elapsed-time hint instead.
+    let elapsed_hint = Some(std::time::Duration::from_millis(elapsed_ms));
 
-    // Get security layer
     let layer = unsafe { &*security_layer };
+    Box::into_raw(Box::new(check_request(layer, request, peer_addr, elapsed_hint)))
+}
 
-    // Run security check synchronously
-    let result = match layer.process_request_sync(&request) {
-        Ok(_) => {
-            // Request allowed
-            SecurityCheckResult {
-                allowed: 1,
-                status_code: 200,
-                error_message: ptr::null(),
-                headers_json: ptr::null(),
-            }
+/// Check a batch of requests in a single FFI crossing, amortizing the
+/// per-call `CStr`/`CString` and [`create_mock_request`] overhead for
+/// high-throughput gateways that would otherwise pay a full boundary
+/// crossing per request. `requests_json` is a JSON array of objects shaped
+/// like `{"method", "url", "headers", "body", "ip", "elapsed_ms"}` (`headers`
+/// is a string-to-string object rather than the single-request entry
+/// point's JSON-encoded-string form, since the whole payload is already
+/// JSON). Returns a [`SecurityCheckResultArray`] that must be freed with
+/// `secureapis_free_result_array`; a malformed `requests_json` yields a
+/// null pointer.
+#[no_mangle]
+pub extern "C" fn secureapis_check_requests_batch(
+    security_layer: *const SecurityLayer,
+    requests_json: *const c_char,
+) -> *mut SecurityCheckResultArray {
+    if security_layer.is_null() {
+        return ptr::null_mut();
+    }
+
+    let requests_str = match unsafe { CStr::from_ptr(requests_json) }.to_str() {
+        Ok(s) => s,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let items: Vec<BatchRequestItem> = match serde_json::from_str(requests_str) {
+        Ok(items) => items,
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let layer = unsafe { &*security_layer };
+
+    // One runtime drives the whole batch concurrently, rather than paying
+    // tokio::runtime::Runtime::new's full OS thread-pool setup cost on every
+    // item the way a loop of secureapis_check_request calls would.
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let results: Vec<SecurityCheckResult> = rt.block_on(async {
+        let checks = items.iter().map(|item| {
+            let request = build_mock_request(&item.method, &item.url, &item.headers, &item.body);
+            let peer_addr: std::net::IpAddr = item
+                .ip
+                .parse()
+                .unwrap_or(std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED));
+            let elapsed_hint = Some(std::time::Duration::from_millis(item.elapsed_ms));
+            check_request_async(layer, request, peer_addr, elapsed_hint)
+        });
+        futures::future::join_all(checks).await
+    });
+
+    let mut results = results.into_boxed_slice();
+    let len = results.len();
+    let ptr = results.as_mut_ptr();
+    std::mem::forget(results);
+
+    Box::into_raw(Box::new(SecurityCheckResultArray { results: ptr, len }))
+}
+
+/// One entry of the JSON array accepted by [`secureapis_check_requests_batch`].
+#[derive(serde::Deserialize)]
+struct BatchRequestItem {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    ip: String,
+    #[serde(default)]
+    elapsed_ms: u64,
+}
+
+/// Run `request` through `layer`'s full CORS + security pipeline, the shared
+/// core of both [`secureapis_check_request`] (via its own single-use
+/// runtime) and [`secureapis_check_requests_batch`] (via one runtime driving
+/// every item in the batch concurrently with `futures::future::join_all`).
+async fn check_request_async(
+    layer: &SecurityLayer,
+    request: http::Request<String>,
+    peer_addr: std::net::IpAddr,
+    elapsed_hint: Option<std::time::Duration>,
+) -> SecurityCheckResult {
+    // Baseline response headers (HSTS, Permissions-Policy, etc., minus
+    // WebSocket-unsafe ones on an upgrade request) apply regardless of the
+    // outcome below, same as the axum middleware.
+    let security_headers = layer.response_headers(&request);
+
+    // CORS preflight/rejection short-circuits before the main pipeline runs,
+    // same as the axum middleware: a preflight has no business reaching
+    // `process_request`, and a rejected origin shouldn't burn rate-limit or
+    // threat-score budget on a request we're about to deny anyway.
+    match layer.evaluate_cors(&request) {
+        Some(crate::cors::CorsOutcome::Preflight(headers)) => {
+            return headers_result(204, &headers, &security_headers);
         }
-        Err(error) => {
-            // Request blocked
-            let (status_code, error_msg) = match error {
-                crate::SecurityError::RateLimitExceeded { retry_after } => {
-                    (429, format!("Rate limit exceeded. Retry after {} seconds", retry_after))
-                }
-                crate::SecurityError::AuthenticationFailed(msg) => (401, msg),
-                crate::SecurityError::AuthorizationFailed(msg) => (403, msg),
-                crate::SecurityError::InvalidInput { reason, .. } => (400, reason),
-                crate::SecurityError::ThreatDetected { threat_type, .. } => {
-                    (403, format!("Threat detected: {}", threat_type))
+        Some(crate::cors::CorsOutcome::Rejected) => {
+            let error_message = match CString::new("CORS origin not allowed") {
+                Ok(cstr) => cstr.into_raw(),
+                Err(_) => ptr::null(),
+            };
+            return SecurityCheckResult {
+                allowed: 0,
+                status_code: 403,
+                error_message,
+                headers_json: headers_json_ptr(&[&security_headers]),
+            };
+        }
+        Some(crate::cors::CorsOutcome::Allowed(headers)) => {
+            if let Some(elapsed) = elapsed_hint {
+                if let Err(error) = layer.check_elapsed(elapsed) {
+                    return block_result(error, &security_headers);
                 }
-                crate::SecurityError::CorsViolation(msg) => (403, msg),
-                crate::SecurityError::CsrfViolation(msg) => (403, msg),
-                crate::SecurityError::HttpsRequired => (403, "HTTPS required".to_string()),
-                _ => (500, "Internal security error".to_string()),
+            }
+            return match layer.process_request(&request, peer_addr).await {
+                Ok(_) => headers_result(200, &headers, &security_headers),
+                Err(error) => block_result(error, &security_headers),
             };
+        }
+        Some(crate::cors::CorsOutcome::NotApplicable) | None => {}
+    }
 
-            let error_cstr = match CString::new(error_msg) {
-                Ok(cstr) => cstr,
-                Err(_) => return ptr::null_mut(),
-            };
+    if let Some(elapsed) = elapsed_hint {
+        if let Err(error) = layer.check_elapsed(elapsed) {
+            return block_result(error, &security_headers);
+        }
+    }
 
-            SecurityCheckResult {
-                allowed: 0,
-                status_code,
-                error_message: error_cstr.into_raw(),
-                headers_json: ptr::null(), // TODO: Add security headers
-            }
+    match layer.process_request(&request, peer_addr).await {
+        Ok(_) => SecurityCheckResult {
+            allowed: 1,
+            status_code: 200,
+            error_message: ptr::null(),
+            headers_json: headers_json_ptr(&[&security_headers]),
+        },
+        Err(error) => block_result(error, &security_headers),
+    }
+}
+
+/// Synchronous wrapper around [`check_request_async`] for
+/// [`secureapis_check_request`]'s single-request entry point.
+fn check_request(
+    layer: &SecurityLayer,
+    request: http::Request<String>,
+    peer_addr: std::net::IpAddr,
+    elapsed_hint: Option<std::time::Duration>,
+) -> SecurityCheckResult {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(check_request_async(layer, request, peer_addr, elapsed_hint))
+}
+
+/// Build a blocked [`SecurityCheckResult`] from a pipeline error.
+///
+/// `error_message` is left null only if the error string itself somehow
+/// contains a NUL byte, which `SecurityError`'s `Display` impl never
+/// produces in practice.
+fn block_result(
+    error: crate::SecurityError,
+    security_headers: &std::collections::HashMap<String, String>,
+) -> SecurityCheckResult {
+    let (status_code, error_msg) = match error {
+        crate::SecurityError::RateLimitExceeded { retry_after } => {
+            (429, format!("Rate limit exceeded. Retry after {} seconds", retry_after))
         }
+        crate::SecurityError::AuthenticationFailed(msg) => (401, msg),
+        crate::SecurityError::AuthorizationFailed(msg) => (403, msg),
+        crate::SecurityError::InvalidInput { reason, .. } => (400, reason),
+        crate::SecurityError::ThreatDetected { threat_type, .. } => {
+            (403, format!("Threat detected: {}", threat_type))
+        }
+        crate::SecurityError::CorsViolation(msg) => (403, msg),
+        crate::SecurityError::CsrfViolation(msg) => (403, msg),
+        crate::SecurityError::HttpsRequired => (403, "HTTPS required".to_string()),
+        crate::SecurityError::RequestTimeout(msg) => (408, msg),
+        _ => (500, "Internal security error".to_string()),
+    };
+
+    let error_message = match CString::new(error_msg) {
+        Ok(cstr) => cstr.into_raw(),
+        Err(_) => ptr::null(),
     };
 
-    Box::into_raw(Box::new(result))
+    SecurityCheckResult {
+        allowed: 0,
+        status_code,
+        error_message,
+        headers_json: headers_json_ptr(&[security_headers]),
+    }
+}
+
+/// Build an allowed [`SecurityCheckResult`] carrying `cors_headers` merged
+/// with `security_headers` as JSON (used for CORS preflight responses and
+/// allowed CORS requests).
+fn headers_result(
+    status_code: i32,
+    cors_headers: &std::collections::HashMap<String, String>,
+    security_headers: &std::collections::HashMap<String, String>,
+) -> SecurityCheckResult {
+    SecurityCheckResult {
+        allowed: 1,
+        status_code,
+        error_message: ptr::null(),
+        headers_json: headers_json_ptr(&[security_headers, cors_headers]),
+    }
+}
+
+/// Merge `maps` (later maps win on key collisions) and serialize to a JSON
+/// string suitable for `SecurityCheckResult.headers_json`, or null if the
+/// combined result is empty.
+fn headers_json_ptr(maps: &[&std::collections::HashMap<String, String>]) -> *const c_char {
+    let mut merged = std::collections::HashMap::new();
+    for map in maps {
+        merged.extend(map.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    if merged.is_empty() {
+        return ptr::null();
+    }
+
+    serde_json::to_string(&merged)
+        .ok()
+        .and_then(|json| CString::new(json).ok())
+        .map(|cstr| cstr.into_raw() as *const c_char)
+        .unwrap_or(ptr::null())
 }
 
 /// Free a security check result
@@ -154,13 +358,51 @@ pub extern "C" fn secureapis_free_result(result: *mut SecurityCheckResult) {
     }
 }
 
-/// Helper function to create a mock HTTP request
+/// Free a [`SecurityCheckResultArray`] returned by
+/// `secureapis_check_requests_batch`, including every result's
+/// `error_message`/`headers_json` strings.
+#[no_mangle]
+pub extern "C" fn secureapis_free_result_array(array: *mut SecurityCheckResultArray) {
+    if array.is_null() {
+        return;
+    }
+
+    unsafe {
+        let array = Box::from_raw(array);
+        let results = Vec::from_raw_parts(array.results, array.len, array.len);
+        for result in &results {
+            if !result.error_message.is_null() {
+                let _ = CString::from_raw(result.error_message as *mut c_char);
+            }
+            if !result.headers_json.is_null() {
+                let _ = CString::from_raw(result.headers_json as *mut c_char);
+            }
+        }
+    }
+}
+
+/// Helper function to create a mock HTTP request from a JSON-encoded headers
+/// string (the shape `secureapis_check_request` accepts its headers in).
 fn create_mock_request(
     method: &str,
     url: &str,
     headers_json: &str,
     body: &str,
-    ip: &str,
+) -> http::Request<String> {
+    let headers = serde_json::from_str::<std::collections::HashMap<String, String>>(headers_json)
+        .unwrap_or_default();
+    build_mock_request(method, url, &headers, body)
+}
+
+/// Build a mock HTTP request from an already-parsed headers map, shared by
+/// [`create_mock_request`] (single-request entry point) and
+/// [`secureapis_check_requests_batch`] (whose headers arrive as a nested JSON
+/// object rather than a JSON-encoded string).
+fn build_mock_request(
+    method: &str,
+    url: &str,
+    headers: &std::collections::HashMap<String, String>,
+    body: &str,
 ) -> http::Request<String> {
     use http::{Method, Uri, Version};
 
@@ -188,20 +430,14 @@ fn create_mock_request(
         .unwrap();
 
     // Add headers
-    if let Ok(headers) = serde_json::from_str::<std::collections::HashMap<String, String>>(headers_json) {
-        for (key, value) in headers {
-            request.headers_mut().insert(
-                http::header::HeaderName::from_bytes(key.as_bytes()).unwrap(),
-                http::header::HeaderValue::from_str(&value).unwrap(),
-            );
+    for (key, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            http::header::HeaderName::from_bytes(key.as_bytes()),
+            http::header::HeaderValue::from_str(value),
+        ) {
+            request.headers_mut().insert(name, value);
         }
     }
 
-    // Add IP as a custom header for security checks
-    request.headers_mut().insert(
-        http::header::HeaderName::from_static("x-forwarded-for"),
-        http::header::HeaderValue::from_str(ip).unwrap(),
-    );
-
     request
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+//! # Baseline security response headers
+//!
+//! Emits the small set of headers that make sense to set unconditionally on
+//! every response: HSTS, a locked-down `Permissions-Policy`, and the classic
+//! `X-Frame-Options`/`X-Content-Type-Options` pair. A WebSocket upgrade
+//! handshake (`Connection: upgrade` + `Upgrade: websocket`) gets a reduced
+//! set, since those three don't make sense on a non-HTML response and can
+//! break the handshake through some reverse proxies.
+
+use crate::config::HeadersConfig;
+use http::{HeaderMap, Request};
+use std::collections::HashMap;
+
+pub struct SecurityHeaders {
+    config: HeadersConfig,
+}
+
+impl SecurityHeaders {
+    pub fn new(config: HeadersConfig) -> Self {
+        Self { config }
+    }
+
+    /// Headers to set on the outgoing response for `request`.
+    pub fn headers_for<B>(&self, request: &Request<B>) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        if !self.config.enabled {
+            return headers;
+        }
+
+        if let Some(hsts) = self.hsts_value() {
+            headers.insert("Strict-Transport-Security".to_string(), hsts);
+        }
+
+        if !is_websocket_upgrade(request.headers()) {
+            headers.insert("X-Frame-Options".to_string(), "DENY".to_string());
+            headers.insert("X-Content-Type-Options".to_string(), "nosniff".to_string());
+
+            if let Some(policy) = self.permissions_policy_value() {
+                headers.insert("Permissions-Policy".to_string(), policy);
+            }
+        }
+
+        headers
+    }
+
+    fn hsts_value(&self) -> Option<String> {
+        let hsts = &self.config.hsts;
+        if !hsts.enabled {
+            return None;
+        }
+
+        let mut value = format!("max-age={}", hsts.max_age_secs);
+        if hsts.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if hsts.preload {
+            value.push_str("; preload");
+        }
+        Some(value)
+    }
+
+    fn permissions_policy_value(&self) -> Option<String> {
+        if self.config.permissions_policy.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.config
+                .permissions_policy
+                .iter()
+                .map(|(directive, allowlist)| format!("{directive}={allowlist}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
+
+/// `Connection: upgrade` + `Upgrade: websocket`, per RFC 6455. `Connection`
+/// is a comma-separated list of tokens, so each is checked individually
+/// rather than comparing the whole header value.
+fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    let connection_upgrades = headers
+        .get(http::header::CONNECTION)
+        .and_then(|h| h.to_str().ok())
+        .map(|v| v.split(',').any(|token| token.trim().eq_ignore_ascii_case("upgrade")))
+        .unwrap_or(false);
+
+    let upgrade_is_websocket = headers
+        .get(http::header::UPGRADE)
+        .and_then(|h| h.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false);
+
+    connection_upgrades && upgrade_is_websocket
+}
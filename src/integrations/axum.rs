@@ -1,12 +1,28 @@
+//! # Axum middleware integration
+//!
+//! Drives [`SecurityLayer`] from an axum request/response cycle: CORS
+//! preflight/rejection short-circuits ahead of the main pipeline, the body
+//! is buffered once and re-wrapped for the downstream handler, and the
+//! configured slow-request/body-read timeouts are enforced with
+//! `tokio::time::timeout` around that work.
+
 use axum::{
+    body::{to_bytes, Body},
     extract::Request,
-    http::StatusCode,
+    http::{HeaderName, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use crate::cors::CorsOutcome;
 use crate::{SecurityConfig, SecurityLayer, SecurityError};
 use std::sync::Arc;
 
+/// Cap on how much of a request body we'll buffer in memory to run it through
+/// `SecurityLayer::process_request` (content-type inspection needs the whole
+/// body available synchronously, which a streaming `Body` doesn't allow).
+/// A body over this limit is rejected outright rather than inspected.
+const MAX_INSPECTABLE_BODY_BYTES: usize = 10 * 1024 * 1024;
+
 /// Axum middleware for security layer
 #[derive(Clone)]
 pub struct AxumSecurityMiddleware {
@@ -21,11 +37,110 @@ impl AxumSecurityMiddleware {
     }
 
     pub async fn handle(&self, request: Request, next: Next) -> Response {
+        // The socket peer address, populated by axum when the router is
+        // served via `into_make_service_with_connect_info::<SocketAddr>()`.
+        // Falls back to unspecified if connect-info wasn't wired up, which
+        // makes every trusted-proxy check fail closed (headers are ignored).
+        let peer_addr = request
+            .extensions()
+            .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+            .map(|connect_info| connect_info.0.ip())
+            .unwrap_or(std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED));
+
+        // CORS is handled ahead of the main pipeline: a preflight never
+        // reaches the real handler, and a rejected origin never gets the
+        // chance to rack up rate-limit/threat-score state for a request we're
+        // about to deny anyway.
+        let cors_outcome = self.security_layer.evaluate_cors(&request);
+        // Baseline headers (HSTS, Permissions-Policy, ...) apply to every
+        // response regardless of outcome, so compute them once up front.
+        let security_headers = self.security_layer.response_headers(&request);
+        match cors_outcome {
+            Some(CorsOutcome::Preflight(ref headers)) => {
+                let mut response = StatusCode::NO_CONTENT.into_response();
+                apply_headers(&mut response, headers);
+                apply_headers(&mut response, &security_headers);
+                return response;
+            }
+            Some(CorsOutcome::Rejected) => {
+                let mut response = (StatusCode::FORBIDDEN, "CORS origin not allowed").into_response();
+                apply_headers(&mut response, &security_headers);
+                return response;
+            }
+            _ => {}
+        }
+
+        // From here on (body read through the downstream handler) is wrapped
+        // in the slow-request timeout, if configured - this is the window a
+        // slow-loris-style client can stall in.
+        let rest = self.handle_body_and_downstream(
+            request,
+            peer_addr,
+            &cors_outcome,
+            security_headers.clone(),
+            next,
+        );
+
+        match self.security_layer.slow_request_timeout() {
+            Some(timeout) => match tokio::time::timeout(timeout, rest).await {
+                Ok(response) => response,
+                Err(_) => {
+                    let mut response = StatusCode::REQUEST_TIMEOUT.into_response();
+                    apply_headers(&mut response, &security_headers);
+                    response
+                }
+            },
+            None => rest.await,
+        }
+    }
+
+    /// Buffer the request body, run it through the security layer, and
+    /// (if allowed) forward to `next`. Split out so `handle` can wrap just
+    /// this part in the slow-request timeout.
+    async fn handle_body_and_downstream(
+        &self,
+        request: Request,
+        peer_addr: std::net::IpAddr,
+        cors_outcome: &Option<CorsOutcome>,
+        security_headers: std::collections::HashMap<String, String>,
+        next: Next,
+    ) -> Response {
+        // Buffer the body: advanced validation's content-decoding needs it
+        // available synchronously, which a streaming `Body` doesn't allow.
+        // The original bytes are re-wrapped into a fresh `Body` below so
+        // `next.run` still sees an ordinary streaming request.
+        let (parts, body) = request.into_parts();
+        let read_body = to_bytes(body, MAX_INSPECTABLE_BODY_BYTES);
+        let body_bytes = match self.security_layer.body_read_timeout() {
+            Some(timeout) => match tokio::time::timeout(timeout, read_body).await {
+                Ok(Ok(bytes)) => bytes,
+                Ok(Err(_)) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+                Err(_) => return StatusCode::REQUEST_TIMEOUT.into_response(),
+            },
+            None => match read_body.await {
+                Ok(bytes) => bytes,
+                Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+            },
+        };
+
+        // `http::request::Parts` isn't `Clone` (it carries `Extensions`), so
+        // build the inspection copy field-by-field instead of cloning it.
+        let mut inspectable_request = Request::new(body_bytes.clone());
+        *inspectable_request.method_mut() = parts.method.clone();
+        *inspectable_request.uri_mut() = parts.uri.clone();
+        *inspectable_request.headers_mut() = parts.headers.clone();
+
         // Process request through security layer
-        match self.security_layer.process_request(&request).await {
+        match self.security_layer.process_request(&inspectable_request, peer_addr).await {
             Ok(_context) => {
                 // Request is valid, continue to next handler
-                next.run(request).await
+                let forwarded_request = Request::from_parts(parts, Body::from(body_bytes));
+                let mut response = next.run(forwarded_request).await;
+                if let Some(CorsOutcome::Allowed(headers)) = cors_outcome {
+                    apply_headers(&mut response, headers);
+                }
+                apply_headers(&mut response, &security_headers);
+                response
             }
             Err(error) => {
                 // Security check failed, return error response
@@ -46,15 +161,33 @@ impl AxumSecurityMiddleware {
                     SecurityError::ThreatDetected { threat_type, .. } => {
                         (StatusCode::FORBIDDEN, format!("Threat detected: {}", threat_type))
                     }
+                    SecurityError::RequestTimeout(msg) => (StatusCode::REQUEST_TIMEOUT, msg),
                     _ => (StatusCode::INTERNAL_SERVER_ERROR, "Internal error".to_string()),
                 };
 
-                (status, message).into_response()
+                let mut response = (status, message).into_response();
+                apply_headers(&mut response, &security_headers);
+                response
             }
         }
     }
 }
 
+/// Insert each header into `response`, skipping any whose name/value doesn't
+/// survive conversion to an HTTP header (malformed config input) rather than
+/// panicking the request.
+fn apply_headers(response: &mut Response, headers: &std::collections::HashMap<String, String>) {
+    for (name, value) in headers {
+        let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) else {
+            continue;
+        };
+        response.headers_mut().insert(name, value);
+    }
+}
+
 /// Extension trait for Axum Router to add security middleware
 pub trait SecurityRouterExt {
     fn with_security(self, config: SecurityConfig) -> Self;
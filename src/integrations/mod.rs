@@ -0,0 +1,7 @@
+//! # Framework integrations
+//!
+//! Thin adapters that drive [`crate::core::SecurityLayer`] from a specific
+//! web framework's request/response types. Each integration is additive —
+//! nothing in `core` depends on them.
+
+pub mod axum;
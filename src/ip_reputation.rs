@@ -0,0 +1,194 @@
+//! # IP reputation (DNSBL-based VPN/proxy/Tor detection)
+//!
+//! Checks a client IP against configured DNS blocklist zones: the IP is
+//! reversed into a lookup label (`<reversed-ip>.<zone>`) and an A-record hit
+//! in `127.0.0.0/8` means listed, with the final octet encoding the category.
+//! Both hits and misses are cached to avoid hammering the resolver on repeat
+//! clients, and a resolver timeout fails open rather than blocking requests -
+//! a timed-out lookup is never itself cached, so a flaky resolver minute
+//! can't memoize a listed IP as clean for the rest of the cache window.
+
+use crate::config::{DnsblZoneConfig, IpReputationConfig};
+use dashmap::DashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use trust_dns_resolver::config::{ResolverConfig, ResolverOpts};
+use trust_dns_resolver::TokioAsyncResolver;
+
+/// The category a listed IP was flagged under. The last octet of the
+/// blocklist's A-record response encodes this (zone-specific convention;
+/// see `classify_octet`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationCategory {
+    Proxy,
+    Vpn,
+    TorExit,
+}
+
+/// Result of an [`IpReputation::check`] call.
+#[derive(Debug, Clone)]
+pub enum ReputationVerdict {
+    Clean,
+    Listed {
+        category: ReputationCategory,
+        zone: String,
+        score: u32,
+    },
+}
+
+#[derive(Clone)]
+struct CacheEntry {
+    verdict: ReputationVerdict,
+    expires_at: Instant,
+}
+
+/// DNSBL-backed VPN/proxy/Tor reputation checker.
+pub struct IpReputation {
+    config: IpReputationConfig,
+    resolver: TokioAsyncResolver,
+    cache: DashMap<IpAddr, CacheEntry>,
+}
+
+impl IpReputation {
+    pub fn new(config: IpReputationConfig) -> Self {
+        let resolver = TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default());
+        Self {
+            config,
+            resolver,
+            cache: DashMap::new(),
+        }
+    }
+
+    /// Check `ip` against every configured zone, in order, returning on the
+    /// first hit.
+    pub async fn check(&self, ip: IpAddr) -> ReputationVerdict {
+        if !self.config.enabled || self.config.zones.is_empty() {
+            return ReputationVerdict::Clean;
+        }
+
+        if let Some(cached) = self.cache.get(&ip) {
+            if cached.expires_at > Instant::now() {
+                return cached.verdict.clone();
+            }
+        }
+
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        // Set if any zone times out, so a clean result below reflects "none of
+        // the zones we actually heard back from had this IP listed" rather
+        // than being memoized as a real answer - otherwise a single flaky
+        // resolver minute would cache a VPN/Tor-listed IP as Clean for the
+        // full `cache_ttl_secs` window.
+        let mut any_timed_out = false;
+
+        for zone in &self.config.zones {
+            let Some(label) = reverse_label(ip, &zone.zone) else {
+                // This zone doesn't support the address family (e.g. a
+                // v4-only DNSBL queried for an IPv6 client) - skip it.
+                continue;
+            };
+
+            match tokio::time::timeout(timeout, self.resolver.lookup_ip(label)).await {
+                Ok(Ok(response)) => {
+                    if let Some(IpAddr::V4(hit)) = response.iter().next() {
+                        if let Some(verdict) = self.to_verdict(hit, zone) {
+                            self.cache_insert(ip, verdict.clone());
+                            return verdict;
+                        }
+                    }
+                }
+                Ok(Err(_)) => {
+                    // NXDOMAIN / no record: not listed under this zone, keep
+                    // trying the rest.
+                }
+                Err(_) => {
+                    // Timed out: fail open rather than let a flaky resolver
+                    // take down the pipeline.
+                    warn!(zone = %zone.zone, %ip, "dnsbl lookup timed out, failing open");
+                    any_timed_out = true;
+                }
+            }
+        }
+
+        // Only memoize an explicit negative answer. If every zone (or any
+        // zone) timed out, this request still proceeds as clean, but we
+        // don't know that for the next `cache_ttl_secs` - skip the cache so
+        // the next request gets a fresh lookup instead of reusing a timeout.
+        if !any_timed_out {
+            self.cache_insert(ip, ReputationVerdict::Clean);
+        }
+        ReputationVerdict::Clean
+    }
+
+    fn to_verdict(&self, hit: Ipv4Addr, zone: &DnsblZoneConfig) -> Option<ReputationVerdict> {
+        let octets = hit.octets();
+        if octets[0] != 127 {
+            // Not a blocklist-format response.
+            return None;
+        }
+
+        let (category, score) = match classify_octet(octets[3]) {
+            ReputationCategory::Proxy => (ReputationCategory::Proxy, zone.proxy_score),
+            ReputationCategory::Vpn => (ReputationCategory::Vpn, zone.vpn_score),
+            ReputationCategory::TorExit => (ReputationCategory::TorExit, zone.tor_score),
+        };
+
+        Some(ReputationVerdict::Listed {
+            category,
+            zone: zone.zone.clone(),
+            score,
+        })
+    }
+
+    fn cache_insert(&self, ip: IpAddr, verdict: ReputationVerdict) {
+        self.cache.insert(
+            ip,
+            CacheEntry {
+                verdict,
+                expires_at: Instant::now() + Duration::from_secs(self.config.cache_ttl_secs),
+            },
+        );
+    }
+}
+
+/// Decode a blocklist response's final octet into a category.
+///
+/// Zone-specific conventions vary; this follows the common pattern of
+/// `.2` = open proxy, `.3` = commercial VPN exit, `.4` = Tor exit, defaulting
+/// unknown codes to `Proxy` (the conservative choice).
+fn classify_octet(octet: u8) -> ReputationCategory {
+    match octet {
+        3 => ReputationCategory::Vpn,
+        4 => ReputationCategory::TorExit,
+        _ => ReputationCategory::Proxy,
+    }
+}
+
+/// Build the DNSBL lookup label for `ip` under `zone`.
+///
+/// IPv4 reverses the dotted octets (`1.2.3.4` -> `4.3.2.1.zone`). IPv6 uses
+/// the nibble-reversed `ip6.arpa`-style form; `None` is returned when `ip` is
+/// v6 and the zone has no v6 support signaled (an `ip6` label after the
+/// zone's first label, e.g. `dnsbl.ip6.example.org` or `example.ip6`),
+/// so callers can skip v4-only zones instead of querying garbage.
+fn reverse_label(ip: IpAddr, zone: &str) -> Option<String> {
+    match ip {
+        IpAddr::V4(v4) => {
+            let o = v4.octets();
+            Some(format!("{}.{}.{}.{}.{}", o[3], o[2], o[1], o[0], zone))
+        }
+        IpAddr::V6(v6) => {
+            if !zone.contains(".ip6.") && !zone.ends_with(".ip6") {
+                return None;
+            }
+            let hex: String = v6.segments().iter().map(|s| format!("{:04x}", s)).collect();
+            let label: String = hex
+                .chars()
+                .rev()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(".");
+            Some(format!("{}.{}", label, zone))
+        }
+    }
+}
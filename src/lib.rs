@@ -3,6 +3,7 @@
 //! A zero-overhead security layer for protecting APIs against common vulnerabilities
 //! and penetration testing attacks.
 
+pub mod audit;
 pub mod config;
 pub mod core;
 pub mod middleware;
@@ -24,16 +25,17 @@ pub mod request_constraints;
 pub mod method_validator;
 pub mod cookie_security;
 pub mod replay_protection;
-// pub mod integrations; // Temporarily disabled due to threading issues
+pub mod integrations;
 pub mod ffi; // Foreign Function Interface for language bindings
 
 // Re-exports for convenience
+pub use audit::{AuditEvent, AuditSink, NoopAuditSink};
 pub use config::{SecurityConfig, RateLimitConfig, ValidationConfig, AuthConfig};
 pub use core::{SecurityLayer, SecurityContext, SecurityError, SecurityResult};
 pub use middleware::{SecurityMiddleware, MiddlewareChain};
 pub use ui::{UIManager, Dashboard, RequestTracker, AlertManager, SettingsManager, MetricsCollector};
 pub use https::HttpsEnforcer;
-pub use cors::CorsEnforcer;
+pub use cors::{CorsEnforcer, CorsOutcome};
 pub use headers::SecurityHeaders;
 pub use csrf::CsrfProtection;
 pub use advanced_validation::AdvancedValidator;
@@ -44,6 +46,7 @@ pub use request_constraints::RequestConstraints;
 pub use method_validator::MethodValidator;
 pub use cookie_security::CookieSecurity;
 pub use replay_protection::ReplayProtection;
+pub use integrations::axum::{AxumSecurityMiddleware, SecurityRouterExt};
 
 /// Prelude module for common imports
 pub mod prelude {
@@ -0,0 +1,35 @@
+//! # Request monitoring
+//!
+//! Lightweight observability hook invoked at the end of a successful
+//! security pipeline run. Kept separate from `ui::state` (which powers the
+//! dashboard) so monitoring can be wired to external systems independently.
+
+use crate::config::MonitoringConfig;
+use crate::core::SecurityContext;
+use http::Request;
+use tracing::info;
+
+/// Records metrics/traces for requests that pass the security pipeline.
+pub struct Monitor {
+    config: MonitoringConfig,
+}
+
+impl Monitor {
+    pub fn new(config: MonitoringConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn log_request<B>(&self, request: &Request<B>, context: &SecurityContext) {
+        if !self.config.enabled {
+            return;
+        }
+        info!(
+            request_id = %context.request_id,
+            client_ip = %context.client_ip,
+            method = %request.method(),
+            path = %request.uri().path(),
+            threat_score = context.threat_score,
+            "request processed"
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! # Rate limiting
+//!
+//! Two backends share the same [`RateLimiter`] front door:
+//!
+//! - **Local**: a per-process fixed window, fine for a single instance.
+//! - **Redis**: a deferred/approximate counter shared across a fleet. The
+//!   hot path consults a local allowance that is periodically refilled from
+//!   (and flushed to) Redis in the background, so most `check` calls make no
+//!   Redis round-trip; only the call that brings a key's pending hits up to
+//!   `flush_every_hits` performs that key's flush inline.
+
+use crate::config::{RateLimitConfig, RedisRateLimitConfig};
+use crate::core::SecurityError;
+use dashmap::DashMap;
+use redis::AsyncCommands;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Why a [`RateLimiter::check`] call was rejected.
+#[derive(Debug, Clone)]
+pub struct RateLimitRejection {
+    /// Seconds the caller should wait before retrying.
+    pub retry_after: u64,
+}
+
+/// Enforces a per-key request budget, locally or via a shared Redis backend.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    backend: Backend,
+}
+
+enum Backend {
+    Local(Mutex<std::collections::HashMap<String, LocalWindow>>),
+    Redis(RedisBackend),
+}
+
+struct LocalWindow {
+    count: u32,
+    reset_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let backend = match config.redis.clone() {
+            Some(redis_cfg) => Backend::Redis(RedisBackend::new(
+                redis_cfg,
+                config.max_requests,
+                config.window_secs,
+            )),
+            None => Backend::Local(Mutex::new(std::collections::HashMap::new())),
+        };
+        Self { config, backend }
+    }
+
+    /// Check whether `key` (typically the client IP) may proceed.
+    pub async fn check(&self, key: &str) -> Result<(), RateLimitRejection> {
+        match &self.backend {
+            Backend::Local(windows) => self.check_local(windows, key).await,
+            Backend::Redis(redis) => redis.check(key).await,
+        }
+    }
+
+    async fn check_local(
+        &self,
+        windows: &Mutex<std::collections::HashMap<String, LocalWindow>>,
+        key: &str,
+    ) -> Result<(), RateLimitRejection> {
+        let mut windows = windows.lock().await;
+        let now = Instant::now();
+        let window = windows.entry(key.to_string()).or_insert_with(|| LocalWindow {
+            count: 0,
+            reset_at: now + Duration::from_secs(self.config.window_secs),
+        });
+
+        if now >= window.reset_at {
+            window.count = 0;
+            window.reset_at = now + Duration::from_secs(self.config.window_secs);
+        }
+
+        if window.count >= self.config.max_requests {
+            let retry_after = (window.reset_at - now).as_secs().max(1);
+            return Err(RateLimitRejection { retry_after });
+        }
+
+        window.count += 1;
+        Ok(())
+    }
+}
+
+/// Per-key local bookkeeping for the deferred Redis limiter.
+struct KeyCounter {
+    /// Requests allowed locally since the last refill, against `limit_hint`.
+    local_hits: AtomicI64,
+    /// Requests still allowed this window per the last-known global count
+    /// (starts at the full limit), refreshed on flush.
+    limit_hint: AtomicI64,
+    /// Hits accumulated since the last flush (the delta to send as `INCRBY`).
+    pending_delta: AtomicI64,
+    /// Remaining TTL (secs) of the Redis key as last read back on flush,
+    /// returned as `retry_after` when a request is rejected.
+    retry_after_hint: AtomicU64,
+}
+
+impl KeyCounter {
+    fn new(limit: u32) -> Self {
+        Self {
+            local_hits: AtomicI64::new(0),
+            limit_hint: AtomicI64::new(limit as i64),
+            pending_delta: AtomicI64::new(0),
+            retry_after_hint: AtomicU64::new(1),
+        }
+    }
+}
+
+struct RedisBackend {
+    client: redis::Client,
+    config: RedisRateLimitConfig,
+    window_secs: u64,
+    max_requests: u32,
+    counters: Arc<DashMap<String, Arc<KeyCounter>>>,
+}
+
+impl RedisBackend {
+    fn new(config: RedisRateLimitConfig, max_requests: u32, window_secs: u64) -> Self {
+        let client = redis::Client::open(config.url.clone())
+            .expect("invalid rate_limit.redis.url");
+        let counters: Arc<DashMap<String, Arc<KeyCounter>>> = Arc::new(DashMap::new());
+
+        // Background flusher: periodically batches every key's accumulated
+        // delta into a single `INCRBY` + `EXPIRE`, then refills each key's
+        // local allowance from the authoritative global count it reads back.
+        let flush_client = client.clone();
+        let flush_counters = Arc::clone(&counters);
+        let flush_interval = Duration::from_millis(config.flush_interval_ms);
+        let flush_window_secs = window_secs;
+        let flush_max_requests = max_requests as i64;
+        let fail_open = config.fail_open;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(flush_interval);
+            loop {
+                ticker.tick().await;
+                let conn = flush_client.get_multiplexed_async_connection().await;
+                let mut conn = match conn {
+                    Ok(conn) => conn,
+                    Err(err) => {
+                        let err = SecurityError::InternalError(format!(
+                            "rate_limit: redis unavailable during flush: {}",
+                            err
+                        ));
+                        if fail_open {
+                            warn!(%err, "failing open");
+                        } else {
+                            for entry in flush_counters.iter() {
+                                entry.value().limit_hint.store(0, Ordering::Release);
+                            }
+                            warn!(%err, "failing closed");
+                        }
+                        continue;
+                    }
+                };
+
+                for entry in flush_counters.iter() {
+                    let key = entry.key().clone();
+                    let counter = Arc::clone(entry.value());
+                    let delta = counter.pending_delta.swap(0, Ordering::AcqRel);
+                    if delta == 0 {
+                        // A key whose allowance is used up gets no new hits to
+                        // flush, so re-read its global count to refill it.
+                        let local_hits = counter.local_hits.load(Ordering::Acquire);
+                        if local_hits < counter.limit_hint.load(Ordering::Acquire) {
+                            continue;
+                        }
+                        let count: redis::RedisResult<Option<i64>> =
+                            conn.get(format!("ratelimit:{}", key)).await;
+                        if let Ok(count) = count {
+                            let remaining = (flush_max_requests - count.unwrap_or(0)).max(0);
+                            counter.limit_hint.store(remaining, Ordering::Release);
+                            counter.local_hits.store(0, Ordering::Release);
+                        }
+                        continue;
+                    }
+
+                    let redis_key = format!("ratelimit:{}", key);
+                    let result: redis::RedisResult<i64> = async {
+                        let count: i64 = conn.incr(&redis_key, delta).await?;
+                        let _: () = conn.expire(&redis_key, flush_window_secs as i64).await?;
+                        Ok(count)
+                    }
+                    .await;
+
+                    match result {
+                        Ok(global_count) => {
+                            let remaining = (flush_max_requests - global_count).max(0);
+                            counter.limit_hint.store(remaining, Ordering::Release);
+                            counter.local_hits.store(0, Ordering::Release);
+
+                            let ttl: redis::RedisResult<i64> = conn.ttl(&redis_key).await;
+                            if let Ok(ttl) = ttl {
+                                counter
+                                    .retry_after_hint
+                                    .store(ttl.max(1) as u64, Ordering::Release);
+                            }
+                        }
+                        Err(err) => {
+                            // Re-queue the delta we just swapped out so the
+                            // next flush (periodic or eager) retries it,
+                            // instead of silently dropping these hits.
+                            counter.pending_delta.fetch_add(delta, Ordering::AcqRel);
+
+                            let err = SecurityError::InternalError(format!(
+                                "rate_limit: redis flush failed for {}: {}",
+                                key, err
+                            ));
+                            if fail_open {
+                                // Leave the local allowance as-is; the next
+                                // successful flush will reconcile it.
+                                warn!(%err, "failing open");
+                            } else {
+                                counter.limit_hint.store(0, Ordering::Release);
+                                warn!(%err, "failing closed");
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            client,
+            config,
+            window_secs,
+            max_requests,
+            counters,
+        }
+    }
+
+    async fn check(&self, key: &str) -> Result<(), RateLimitRejection> {
+        let counter = self
+            .counters
+            .entry(key.to_string())
+            .or_insert_with(|| Arc::new(KeyCounter::new(self.max_requests)))
+            .clone();
+
+        let hits = counter.local_hits.fetch_add(1, Ordering::AcqRel) + 1;
+        let limit_hint = counter.limit_hint.load(Ordering::Acquire);
+
+        if hits > limit_hint {
+            counter.local_hits.fetch_sub(1, Ordering::AcqRel);
+            return Err(RateLimitRejection {
+                retry_after: counter.retry_after_hint.load(Ordering::Acquire),
+            });
+        }
+
+        let pending = counter.pending_delta.fetch_add(1, Ordering::AcqRel) + 1;
+        if pending as u32 >= self.config.flush_every_hits {
+            self.flush_now(key, &counter).await;
+        }
+
+        Ok(())
+    }
+
+    /// Eagerly flush one key's delta, used when a key is hot enough to hit
+    /// `flush_every_hits` before the background ticker fires.
+    async fn flush_now(&self, key: &str, counter: &Arc<KeyCounter>) {
+        let conn = self.client.get_multiplexed_async_connection().await;
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(err) => {
+                let err = SecurityError::InternalError(format!(
+                    "rate_limit: redis unavailable during eager flush for {}: {}",
+                    key, err
+                ));
+                if self.config.fail_open {
+                    warn!(%err, "failing open");
+                } else {
+                    counter.limit_hint.store(0, Ordering::Release);
+                    warn!(%err, "failing closed");
+                }
+                return;
+            }
+        };
+
+        let delta = counter.pending_delta.swap(0, Ordering::AcqRel);
+        if delta == 0 {
+            return;
+        }
+
+        let redis_key = format!("ratelimit:{}", key);
+        let result: redis::RedisResult<i64> = async {
+            let count: i64 = conn.incr(&redis_key, delta).await?;
+            let _: () = conn.expire(&redis_key, self.window_secs as i64).await?;
+            Ok(count)
+        }
+        .await;
+
+        match result {
+            Ok(global_count) => {
+                let remaining = (self.max_requests as i64 - global_count).max(0);
+                counter.limit_hint.store(remaining, Ordering::Release);
+                counter.local_hits.store(0, Ordering::Release);
+
+                let ttl: redis::RedisResult<i64> = conn.ttl(&redis_key).await;
+                if let Ok(ttl) = ttl {
+                    counter
+                        .retry_after_hint
+                        .store(ttl.max(1) as u64, Ordering::Release);
+                }
+            }
+            Err(err) => {
+                // Re-queue the delta we just swapped out so a later flush
+                // retries it, and honor the same fail-open/fail-closed
+                // policy as the periodic flush loop.
+                counter.pending_delta.fetch_add(delta, Ordering::AcqRel);
+
+                let err = SecurityError::InternalError(format!(
+                    "rate_limit: redis eager flush failed for {}: {}",
+                    key, err
+                ));
+                if self.config.fail_open {
+                    warn!(%err, "failing open");
+                } else {
+                    counter.limit_hint.store(0, Ordering::Release);
+                    warn!(%err, "failing closed");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! # Replay protection
+//!
+//! Opt-in request signing: the client signs `method || path || timestamp ||
+//! nonce` with a shared secret, and this stage rejects anything outside the
+//! allowed clock skew or carrying a nonce it has already seen.
+
+use crate::config::ReplayProtectionConfig;
+use crate::core::{SecurityContext, SecurityError, SecurityResult};
+use hmac::{Hmac, Mac};
+use http::Request;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Validates HMAC-signed requests and rejects replays.
+pub struct ReplayProtection {
+    config: ReplayProtectionConfig,
+    /// Nonces seen recently enough that their timestamp could still pass the
+    /// skew check. The TTL bounds memory: once a nonce's timestamp falls
+    /// outside the skew window it would be rejected on that basis anyway, so
+    /// there's no need to remember it any longer.
+    seen_nonces: Mutex<HashMap<String, Instant>>,
+}
+
+impl ReplayProtection {
+    pub fn new(config: ReplayProtectionConfig) -> Self {
+        Self {
+            config,
+            seen_nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Validate a signed request, recording a threat score on `context` and
+    /// returning `SecurityError::ReplayDetected` on any failure.
+    pub async fn validate<B>(
+        &self,
+        request: &Request<B>,
+        context: &mut SecurityContext,
+    ) -> SecurityResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let headers = request.headers();
+        let header_str = |name: &str| headers.get(name).and_then(|h| h.to_str().ok());
+
+        let (Some(signature), Some(timestamp_str), Some(nonce), Some(api_key)) = (
+            header_str("x-signature"),
+            header_str("x-timestamp"),
+            header_str("x-nonce"),
+            header_str("x-api-key"),
+        ) else {
+            return self.reject(context, "missing signature headers".to_string());
+        };
+
+        let Some(secret) = self.config.secrets.get(api_key) else {
+            return self.reject(context, format!("unknown api key: {api_key}"));
+        };
+
+        let Ok(timestamp) = timestamp_str.parse::<i64>() else {
+            return self.reject(context, "malformed timestamp".to_string());
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        if (now - timestamp).abs() as u64 > self.config.skew_secs {
+            return self.reject(context, "timestamp outside allowed skew".to_string());
+        }
+
+        let expected = HmacSha256::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length")
+            .chain_update(request.method().as_str().as_bytes())
+            .chain_update(request.uri().path().as_bytes())
+            .chain_update(timestamp_str.as_bytes())
+            .chain_update(nonce.as_bytes());
+
+        let Ok(provided) = hex::decode(signature) else {
+            return self.reject(context, "malformed signature encoding".to_string());
+        };
+
+        if expected.verify_slice(&provided).is_err() {
+            return self.reject(context, "signature mismatch".to_string());
+        }
+
+        let nonce_key = format!("{api_key}:{nonce}");
+        let mut seen = self.seen_nonces.lock().await;
+        seen.retain(|_, expiry| *expiry > Instant::now());
+
+        if seen.contains_key(&nonce_key) {
+            drop(seen);
+            return self.reject(context, "nonce already used".to_string());
+        }
+
+        // Remember the nonce for up to twice the skew window: that's the
+        // longest a still-valid timestamp could be presented again from now.
+        let ttl = Duration::from_secs(self.config.skew_secs.saturating_mul(2).max(1));
+        seen.insert(nonce_key, Instant::now() + ttl);
+
+        Ok(())
+    }
+
+    fn reject(&self, context: &mut SecurityContext, reason: String) -> SecurityResult<()> {
+        context.add_threat_score(self.config.score_on_violation);
+        Err(SecurityError::ReplayDetected(reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API_KEY: &str = "test-key";
+    const SECRET: &str = "shared-secret";
+
+    fn config() -> ReplayProtectionConfig {
+        let mut secrets = HashMap::new();
+        secrets.insert(API_KEY.to_string(), SECRET.to_string());
+        ReplayProtectionConfig {
+            enabled: true,
+            skew_secs: 300,
+            secrets,
+            score_on_violation: 60,
+        }
+    }
+
+    fn now_secs() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+
+    fn signed_request(method: &str, path: &str, timestamp: i64, nonce: &str) -> Request<()> {
+        let timestamp_str = timestamp.to_string();
+        let signature = HmacSha256::new_from_slice(SECRET.as_bytes())
+            .unwrap()
+            .chain_update(method.as_bytes())
+            .chain_update(path.as_bytes())
+            .chain_update(timestamp_str.as_bytes())
+            .chain_update(nonce.as_bytes())
+            .finalize()
+            .into_bytes();
+
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .header("x-signature", hex::encode(signature))
+            .header("x-timestamp", timestamp_str)
+            .header("x-nonce", nonce)
+            .header("x-api-key", API_KEY)
+            .body(())
+            .unwrap()
+    }
+
+    fn context() -> SecurityContext {
+        SecurityContext::new("req-1".to_string(), "127.0.0.1".to_string())
+    }
+
+    #[tokio::test]
+    async fn accepts_a_correctly_signed_request() {
+        let replay = ReplayProtection::new(config());
+        let mut ctx = context();
+        let request = signed_request("GET", "/orders", now_secs(), "nonce-1");
+
+        assert!(replay.validate(&request, &mut ctx).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn rejects_timestamp_outside_allowed_skew() {
+        let replay = ReplayProtection::new(config());
+        let mut ctx = context();
+        let request = signed_request("GET", "/orders", now_secs() - 3600, "nonce-2");
+
+        let result = replay.validate(&request, &mut ctx).await;
+
+        assert!(matches!(result, Err(SecurityError::ReplayDetected(_))));
+        assert_eq!(ctx.threat_score, 60);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_timestamp_from_the_future() {
+        let replay = ReplayProtection::new(config());
+        let mut ctx = context();
+        let request = signed_request("GET", "/orders", now_secs() + 3600, "nonce-3");
+
+        let result = replay.validate(&request, &mut ctx).await;
+
+        assert!(matches!(result, Err(SecurityError::ReplayDetected(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_replayed_nonce() {
+        let replay = ReplayProtection::new(config());
+        let mut first_ctx = context();
+        let mut second_ctx = context();
+        let timestamp = now_secs();
+        let first = signed_request("GET", "/orders", timestamp, "nonce-4");
+        let second = signed_request("GET", "/orders", timestamp, "nonce-4");
+
+        assert!(replay.validate(&first, &mut first_ctx).await.is_ok());
+        let result = replay.validate(&second, &mut second_ctx).await;
+
+        assert!(matches!(result, Err(SecurityError::ReplayDetected(_))));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_tampered_signature() {
+        let replay = ReplayProtection::new(config());
+        let mut ctx = context();
+        let mut request = signed_request("GET", "/orders", now_secs(), "nonce-5");
+        request.headers_mut().insert(
+            "x-signature",
+            http::HeaderValue::from_static(
+                "0000000000000000000000000000000000000000000000000000000000000000",
+            ),
+        );
+
+        let result = replay.validate(&request, &mut ctx).await;
+
+        assert!(matches!(result, Err(SecurityError::ReplayDetected(_))));
+    }
+}
@@ -0,0 +1,71 @@
+//! # Slow-request / slow-loris timeout guard
+//!
+//! Bounds how long a client is allowed to take delivering a full request,
+//! defending against slow-loris-style attacks: a single `slow_request_timeout`
+//! for the whole exchange (body read through the downstream handler), plus a
+//! narrower `body_read_timeout` for just the body read. `crate::integrations::axum`
+//! enforces both with `tokio::time::timeout`; `crate::ffi` has no event loop
+//! to do that with, so it takes a caller-supplied elapsed-time hint instead
+//! and checks it against `slow_request_timeout` directly.
+//!
+//! `header_read_timeout` is deliberately *not* enforced here: by the time an
+//! axum middleware (or the FFI caller) sees a `Request`, the headers have
+//! already been fully parsed by the transport that accepted the connection
+//! (hyper, or whatever terminated it ahead of the FFI caller) - there's no
+//! phase left at this layer to time. The budget exists for a transport-level
+//! integration (e.g. a hyper `Builder::http1().header_read_timeout`-style
+//! hook) to read, not for `RequestConstraints` to apply itself.
+
+use crate::config::RequestConstraintsConfig;
+use crate::core::{SecurityError, SecurityResult};
+use std::time::Duration;
+
+pub struct RequestConstraints {
+    config: RequestConstraintsConfig,
+}
+
+impl RequestConstraints {
+    pub fn new(config: RequestConstraintsConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn slow_request_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.slow_request_timeout_secs)
+    }
+
+    /// Not enforced by this crate - see the module docs. Exposed so a
+    /// transport-level integration can read the configured budget.
+    pub fn header_read_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.header_read_timeout_secs)
+    }
+
+    /// Enforced by `crate::integrations::axum` around the body-buffering
+    /// read, narrower than `slow_request_timeout` (which also covers the
+    /// downstream handler).
+    pub fn body_read_timeout(&self) -> Duration {
+        Duration::from_secs(self.config.body_read_timeout_secs)
+    }
+
+    /// Check a caller-supplied elapsed time against `slow_request_timeout`,
+    /// for transports (the synchronous FFI path) with no event loop of their
+    /// own to enforce a `tokio::time::timeout` on.
+    pub fn check_elapsed(&self, elapsed: Duration) -> SecurityResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let limit = self.slow_request_timeout();
+        if elapsed > limit {
+            return Err(SecurityError::RequestTimeout(format!(
+                "request took {:.1}s, exceeding the {:.1}s limit",
+                elapsed.as_secs_f64(),
+                limit.as_secs_f64(),
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,578 @@
+//! # Threat detection rule engine
+//!
+//! Replaces a fixed set of hardcoded substring checks with a declarative
+//! ruleset loaded from config: each [`ThreatRule`] carries a category, a score
+//! weight, and a severity. Scores accumulate per category; a single category
+//! crossing `combo_threshold` is the "obvious attack combo" path, and the
+//! total across all categories crossing `block_threshold` is the fallback.
+
+use crate::config::{RuleFieldConfig, RuleMatcherConfig, ThreatDetectionConfig, ThreatRuleConfig};
+use crate::core::ThreatSeverity;
+use http::HeaderMap;
+use regex::Regex;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// The class of attack a rule detects, used to accumulate per-category score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleCategory {
+    SqlInjection,
+    Xss,
+    PathTraversal,
+    CommandInjection,
+    ScannerTool,
+    Other,
+}
+
+impl RuleCategory {
+    fn parse(s: &str) -> Self {
+        match s {
+            "sql_injection" => Self::SqlInjection,
+            "xss" => Self::Xss,
+            "path_traversal" => Self::PathTraversal,
+            "command_injection" => Self::CommandInjection,
+            "scanner_tool" => Self::ScannerTool,
+            _ => Self::Other,
+        }
+    }
+}
+
+fn parse_severity(s: &str) -> ThreatSeverity {
+    match s {
+        "low" => ThreatSeverity::Low,
+        "high" => ThreatSeverity::High,
+        "critical" => ThreatSeverity::Critical,
+        _ => ThreatSeverity::Medium,
+    }
+}
+
+/// Which part of the request a rule was evaluated against.
+enum RuleField {
+    Uri,
+    Body,
+    Header(Option<String>),
+}
+
+/// The parts of a request a rule can inspect, gathered once per request.
+pub struct RuleTarget<'a> {
+    pub uri: &'a str,
+    pub headers: &'a HeaderMap,
+    pub body: Option<&'a str>,
+}
+
+impl<'a> RuleTarget<'a> {
+    fn values(&self, field: &RuleField) -> Vec<String> {
+        match field {
+            RuleField::Uri => vec![self.uri.to_string()],
+            RuleField::Body => self.body.map(|b| vec![b.to_string()]).unwrap_or_default(),
+            RuleField::Header(Some(name)) => self
+                .headers
+                .get_all(name.as_str())
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .map(str::to_string)
+                .collect(),
+            RuleField::Header(None) => self
+                .headers
+                .values()
+                .filter_map(|v| v.to_str().ok())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// A single detection signature: a category, a score, a severity, and a
+/// match predicate over some part of the request.
+pub trait ThreatRule: Send + Sync {
+    fn name(&self) -> &str;
+    fn category(&self) -> RuleCategory;
+    fn score(&self) -> u32;
+    fn severity(&self) -> ThreatSeverity;
+    fn is_match(&self, target: &RuleTarget) -> bool;
+}
+
+struct SubstringRule {
+    name: String,
+    category: RuleCategory,
+    score: u32,
+    severity: ThreatSeverity,
+    field: RuleField,
+    needles: Vec<String>,
+    case_insensitive: bool,
+}
+
+impl ThreatRule for SubstringRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn category(&self) -> RuleCategory {
+        self.category
+    }
+    fn score(&self) -> u32 {
+        self.score
+    }
+    fn severity(&self) -> ThreatSeverity {
+        self.severity
+    }
+    fn is_match(&self, target: &RuleTarget) -> bool {
+        target.values(&self.field).iter().any(|value| {
+            let value = if self.case_insensitive {
+                value.to_lowercase()
+            } else {
+                value.clone()
+            };
+            self.needles.iter().any(|needle| {
+                let needle = if self.case_insensitive {
+                    needle.to_lowercase()
+                } else {
+                    needle.clone()
+                };
+                value.contains(&needle)
+            })
+        })
+    }
+}
+
+/// Like [`SubstringRule`], but only matches when *every* needle is present
+/// in the same value (AND semantics) rather than any one of them. Used for
+/// signatures where a single needle is too common to score on its own but
+/// the combination (e.g. `union` + `select`) is a meaningful signal.
+struct AllSubstringsRule {
+    name: String,
+    category: RuleCategory,
+    score: u32,
+    severity: ThreatSeverity,
+    field: RuleField,
+    needles: Vec<String>,
+    case_insensitive: bool,
+}
+
+impl ThreatRule for AllSubstringsRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn category(&self) -> RuleCategory {
+        self.category
+    }
+    fn score(&self) -> u32 {
+        self.score
+    }
+    fn severity(&self) -> ThreatSeverity {
+        self.severity
+    }
+    fn is_match(&self, target: &RuleTarget) -> bool {
+        target.values(&self.field).iter().any(|value| {
+            let value = if self.case_insensitive {
+                value.to_lowercase()
+            } else {
+                value.clone()
+            };
+            self.needles.iter().all(|needle| {
+                let needle = if self.case_insensitive {
+                    needle.to_lowercase()
+                } else {
+                    needle.clone()
+                };
+                value.contains(&needle)
+            })
+        })
+    }
+}
+
+struct RegexRule {
+    name: String,
+    category: RuleCategory,
+    score: u32,
+    severity: ThreatSeverity,
+    field: RuleField,
+    pattern: Regex,
+}
+
+impl ThreatRule for RegexRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn category(&self) -> RuleCategory {
+        self.category
+    }
+    fn score(&self) -> u32 {
+        self.score
+    }
+    fn severity(&self) -> ThreatSeverity {
+        self.severity
+    }
+    fn is_match(&self, target: &RuleTarget) -> bool {
+        target
+            .values(&self.field)
+            .iter()
+            .any(|value| self.pattern.is_match(value))
+    }
+}
+
+/// Flags a request carrying an unusually large header value, a lightweight
+/// signal for buffer-overflow probing tools.
+struct LargeHeaderRule {
+    threshold: usize,
+}
+
+impl ThreatRule for LargeHeaderRule {
+    fn name(&self) -> &str {
+        "oversized-header"
+    }
+    fn category(&self) -> RuleCategory {
+        RuleCategory::Other
+    }
+    fn score(&self) -> u32 {
+        30
+    }
+    fn severity(&self) -> ThreatSeverity {
+        ThreatSeverity::Low
+    }
+    fn is_match(&self, target: &RuleTarget) -> bool {
+        target
+            .headers
+            .values()
+            .any(|v| v.len() > self.threshold)
+    }
+}
+
+/// Flags the combination of shell metacharacters on an API path, which is
+/// meaningfully more specific than either indicator alone.
+struct CommandInjectionComboRule;
+
+impl ThreatRule for CommandInjectionComboRule {
+    fn name(&self) -> &str {
+        "shell-metachar-on-api-path"
+    }
+    fn category(&self) -> RuleCategory {
+        RuleCategory::CommandInjection
+    }
+    fn score(&self) -> u32 {
+        50
+    }
+    fn severity(&self) -> ThreatSeverity {
+        ThreatSeverity::High
+    }
+    fn is_match(&self, target: &RuleTarget) -> bool {
+        (target.uri.contains("`;") || target.uri.contains("`|")) && target.uri.contains("/api/")
+    }
+}
+
+/// The outcome of running every rule against a [`RuleTarget`].
+pub struct EvaluationOutcome {
+    pub total_score: u32,
+    pub fired_rules: Vec<String>,
+    pub severity: ThreatSeverity,
+    /// Highest score any single category accumulated, for the "obvious
+    /// attack combo" path - must not be conflated with `total_score`, which
+    /// sums across categories and would let unrelated low-score hits in
+    /// different categories add up to a false combo.
+    max_category_score: u32,
+    combo_threshold: u32,
+    block_threshold: u32,
+    monitor_only: bool,
+}
+
+impl EvaluationOutcome {
+    /// Whether this evaluation should result in a block, honoring monitor-only mode.
+    pub fn should_block(&self) -> bool {
+        if self.monitor_only || self.fired_rules.is_empty() {
+            return false;
+        }
+        self.max_category_score >= self.combo_threshold || self.total_score >= self.block_threshold
+    }
+
+    /// A human-readable description of which rule(s) fired, for the
+    /// `ThreatDetected::threat_type` field.
+    pub fn threat_type(&self) -> String {
+        self.fired_rules.join(", ")
+    }
+}
+
+/// Evaluates a request against a configured (or default) set of [`ThreatRule`]s.
+pub struct RuleEngine {
+    rules: Vec<Box<dyn ThreatRule>>,
+    monitor_only: bool,
+    combo_threshold: u32,
+    block_threshold: u32,
+}
+
+impl RuleEngine {
+    pub fn new(config: &ThreatDetectionConfig) -> Self {
+        let rules = if config.rules.is_empty() {
+            default_rules()
+        } else {
+            config.rules.iter().filter_map(compile_rule).collect()
+        };
+
+        Self {
+            rules,
+            monitor_only: config.monitor_only,
+            combo_threshold: config.combo_threshold,
+            block_threshold: config.block_threshold,
+        }
+    }
+
+    pub fn evaluate(&self, target: &RuleTarget) -> EvaluationOutcome {
+        let mut category_scores: HashMap<RuleCategory, u32> = HashMap::new();
+        let mut fired_rules = Vec::new();
+        let mut severity = ThreatSeverity::Low;
+
+        for rule in &self.rules {
+            if rule.is_match(target) {
+                *category_scores.entry(rule.category()).or_insert(0) += rule.score();
+                fired_rules.push(rule.name().to_string());
+                if rule.severity() > severity {
+                    severity = rule.severity();
+                }
+            }
+        }
+
+        EvaluationOutcome {
+            total_score: category_scores.values().sum(),
+            max_category_score: category_scores.values().copied().max().unwrap_or(0),
+            fired_rules,
+            severity,
+            combo_threshold: self.combo_threshold,
+            block_threshold: self.block_threshold,
+            monitor_only: self.monitor_only,
+        }
+    }
+}
+
+fn compile_field(field: &RuleFieldConfig) -> RuleField {
+    match field {
+        RuleFieldConfig::Uri => RuleField::Uri,
+        RuleFieldConfig::Body => RuleField::Body,
+        RuleFieldConfig::Header { name } => RuleField::Header(name.clone()),
+    }
+}
+
+fn compile_rule(config: &ThreatRuleConfig) -> Option<Box<dyn ThreatRule>> {
+    let category = RuleCategory::parse(&config.category);
+    let severity = parse_severity(&config.severity);
+    let field = compile_field(&config.field);
+
+    match &config.matcher {
+        RuleMatcherConfig::Substring {
+            needle,
+            case_insensitive,
+        } => Some(Box::new(SubstringRule {
+            name: config.name.clone(),
+            category,
+            score: config.score,
+            severity,
+            field,
+            needles: vec![needle.clone()],
+            case_insensitive: *case_insensitive,
+        })),
+        RuleMatcherConfig::AllSubstrings {
+            needles,
+            case_insensitive,
+        } => Some(Box::new(AllSubstringsRule {
+            name: config.name.clone(),
+            category,
+            score: config.score,
+            severity,
+            field,
+            needles: needles.clone(),
+            case_insensitive: *case_insensitive,
+        })),
+        RuleMatcherConfig::Regex { pattern } => match Regex::new(pattern) {
+            Ok(pattern) => Some(Box::new(RegexRule {
+                name: config.name.clone(),
+                category,
+                score: config.score,
+                severity,
+                field,
+                pattern,
+            })),
+            Err(err) => {
+                warn!(rule = %config.name, error = %err, "ignoring rule with invalid regex");
+                None
+            }
+        },
+    }
+}
+
+/// The baseline ruleset, equivalent to the heuristics this engine replaced.
+/// Used whenever no ruleset is configured.
+fn default_rules() -> Vec<Box<dyn ThreatRule>> {
+    vec![
+        Box::new(SubstringRule {
+            name: "path-traversal".to_string(),
+            category: RuleCategory::PathTraversal,
+            score: 40,
+            severity: ThreatSeverity::High,
+            field: RuleField::Uri,
+            needles: vec!["../../../".to_string(), "..\\..\\..\\".to_string()],
+            case_insensitive: false,
+        }),
+        Box::new(SubstringRule {
+            name: "path-traversal-encoded".to_string(),
+            category: RuleCategory::PathTraversal,
+            score: 50,
+            severity: ThreatSeverity::High,
+            field: RuleField::Uri,
+            needles: vec!["..%2f".to_string(), "..%5c".to_string()],
+            case_insensitive: true,
+        }),
+        Box::new(SubstringRule {
+            name: "xss-script-tag".to_string(),
+            category: RuleCategory::Xss,
+            score: 60,
+            severity: ThreatSeverity::High,
+            field: RuleField::Uri,
+            needles: vec!["<script".to_string(), "javascript:alert".to_string()],
+            case_insensitive: true,
+        }),
+        Box::new(SubstringRule {
+            name: "xss-event-handler".to_string(),
+            category: RuleCategory::Xss,
+            score: 50,
+            severity: ThreatSeverity::High,
+            field: RuleField::Uri,
+            needles: vec!["onerror=".to_string(), "onload=".to_string()],
+            case_insensitive: true,
+        }),
+        Box::new(AllSubstringsRule {
+            name: "sqli-union-select".to_string(),
+            category: RuleCategory::SqlInjection,
+            score: 60,
+            severity: ThreatSeverity::High,
+            field: RuleField::Uri,
+            needles: vec!["union".to_string(), "select".to_string()],
+            case_insensitive: true,
+        }),
+        Box::new(SubstringRule {
+            name: "sqli-stacked-query".to_string(),
+            category: RuleCategory::SqlInjection,
+            score: 60,
+            severity: ThreatSeverity::High,
+            field: RuleField::Uri,
+            needles: vec!["'; drop".to_string(), "'; delete".to_string()],
+            case_insensitive: true,
+        }),
+        Box::new(SubstringRule {
+            name: "sqli-tautology".to_string(),
+            category: RuleCategory::SqlInjection,
+            score: 60,
+            severity: ThreatSeverity::High,
+            field: RuleField::Uri,
+            needles: vec!["' or '1'='1".to_string(), "1'or'1'='1".to_string()],
+            case_insensitive: true,
+        }),
+        Box::new(CommandInjectionComboRule),
+        Box::new(LargeHeaderRule { threshold: 8192 }),
+        Box::new(SubstringRule {
+            name: "known-scanner-user-agent".to_string(),
+            category: RuleCategory::ScannerTool,
+            score: 70,
+            severity: ThreatSeverity::Medium,
+            field: RuleField::Header(Some("user-agent".to_string())),
+            needles: vec![
+                "sqlmap".to_string(),
+                "nikto".to_string(),
+                "nmap".to_string(),
+                "masscan".to_string(),
+                "burp".to_string(),
+            ],
+            case_insensitive: true,
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ThreatDetectionConfig;
+
+    fn engine() -> RuleEngine {
+        RuleEngine::new(&ThreatDetectionConfig {
+            enabled: true,
+            block_suspicious: true,
+            rules: vec![],
+            monitor_only: false,
+            combo_threshold: 40,
+            block_threshold: 100,
+        })
+    }
+
+    fn target(uri: &str, headers: &HeaderMap) -> RuleTarget<'_> {
+        RuleTarget {
+            uri,
+            headers,
+            body: None,
+        }
+    }
+
+    #[test]
+    fn lone_select_keyword_does_not_trigger_sqli_combo() {
+        let engine = engine();
+        let headers = HeaderMap::new();
+        let outcome = engine.evaluate(&target("/items?select=id,name", &headers));
+
+        assert!(!outcome.fired_rules.contains(&"sqli-union-select".to_string()));
+        assert!(!outcome.should_block());
+    }
+
+    #[test]
+    fn union_and_select_together_trigger_sqli_combo() {
+        let engine = engine();
+        let headers = HeaderMap::new();
+        let outcome = engine.evaluate(&target("/items?q=union+select+*+from+users", &headers));
+
+        assert!(outcome.fired_rules.contains(&"sqli-union-select".to_string()));
+        assert!(outcome.should_block());
+    }
+
+    /// Two hits in *different* categories, neither reaching `combo_threshold`
+    /// on its own, must not trigger the "obvious attack combo" path just
+    /// because their sum happens to cross it - that's what `block_threshold`
+    /// is for, and it's set well above this sum here.
+    #[test]
+    fn unrelated_low_scores_across_categories_do_not_trigger_combo() {
+        use crate::config::{RuleFieldConfig, RuleMatcherConfig, ThreatRuleConfig};
+
+        let rules = vec![
+            ThreatRuleConfig {
+                name: "low-other".to_string(),
+                category: "other".to_string(),
+                field: RuleFieldConfig::Uri,
+                matcher: RuleMatcherConfig::Substring {
+                    needle: "foo".to_string(),
+                    case_insensitive: false,
+                },
+                score: 20,
+                severity: "low".to_string(),
+            },
+            ThreatRuleConfig {
+                name: "low-scanner".to_string(),
+                category: "scanner_tool".to_string(),
+                field: RuleFieldConfig::Uri,
+                matcher: RuleMatcherConfig::Substring {
+                    needle: "bar".to_string(),
+                    case_insensitive: false,
+                },
+                score: 20,
+                severity: "low".to_string(),
+            },
+        ];
+
+        let engine = RuleEngine::new(&ThreatDetectionConfig {
+            enabled: true,
+            block_suspicious: true,
+            rules,
+            monitor_only: false,
+            combo_threshold: 40,
+            block_threshold: 100,
+        });
+
+        let headers = HeaderMap::new();
+        let outcome = engine.evaluate(&target("/items?foo=1&bar=2", &headers));
+
+        assert_eq!(outcome.total_score, 40);
+        assert!(!outcome.should_block());
+    }
+}
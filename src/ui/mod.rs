@@ -0,0 +1,89 @@
+//! # Dashboard UI
+//!
+//! State and controllers backing the operator-facing live dashboard.
+//! `state` holds the data; the types below are thin views over it.
+
+pub mod state;
+
+use std::sync::Arc;
+
+pub use state::UIState;
+
+/// Top-level dashboard façade.
+pub struct Dashboard {
+    pub state: Arc<UIState>,
+}
+
+impl Dashboard {
+    pub fn new(state: Arc<UIState>) -> Self {
+        Self { state }
+    }
+}
+
+/// Top-level UI entry point; owns a [`Dashboard`] over a fresh [`UIState`].
+pub struct UIManager {
+    pub dashboard: Dashboard,
+}
+
+impl UIManager {
+    pub fn new() -> Self {
+        let state = Arc::new(UIState::new());
+        Self {
+            dashboard: Dashboard::new(state),
+        }
+    }
+}
+
+impl Default for UIManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read-side access to the rolling request log.
+pub struct RequestTracker {
+    state: Arc<UIState>,
+}
+
+impl RequestTracker {
+    pub fn new(state: Arc<UIState>) -> Self {
+        Self { state }
+    }
+
+    pub async fn recent(&self, limit: usize) -> Vec<state::RequestLog> {
+        self.state.recent_logs(limit).await
+    }
+}
+
+/// Holds a handle to the shared dashboard state; it defines no alert
+/// operations.
+pub struct AlertManager {
+    state: Arc<UIState>,
+}
+
+impl AlertManager {
+    pub fn new(state: Arc<UIState>) -> Self {
+        Self { state }
+    }
+}
+
+/// Dashboard settings placeholder; it holds no settings.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsManager;
+
+/// Aggregates counters into dashboard-ready metrics.
+pub struct MetricsCollector {
+    state: Arc<UIState>,
+}
+
+impl MetricsCollector {
+    pub fn new(state: Arc<UIState>) -> Self {
+        Self { state }
+    }
+
+    pub fn total_requests(&self) -> u64 {
+        self.state
+            .total_requests
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,61 @@
+//! # Shared UI state
+//!
+//! The data backing the live dashboard: rolling request log plus coarse
+//! counters updated from the hot path with relaxed atomics.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
+use tokio::sync::Mutex;
+
+/// A single logged request, as shown in the dashboard's request table.
+#[derive(Debug, Clone)]
+pub struct RequestLog {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    pub client_ip: String,
+    pub user_agent: String,
+    pub user_id: Option<String>,
+    pub status_code: u16,
+    pub response_time_ms: f64,
+    pub threat_score: f64,
+    pub blocked: bool,
+    pub reason: Option<String>,
+    pub headers: HashMap<String, String>,
+}
+
+/// Maximum number of request logs retained in memory.
+const MAX_LOG_ENTRIES: usize = 1000;
+
+/// Shared, mutex-and-atomics-backed state read by the dashboard.
+#[derive(Default)]
+pub struct UIState {
+    pub total_requests: AtomicU64,
+    pub blocked_requests: AtomicU64,
+    pub rate_limited: AtomicU64,
+    pub auth_failures: AtomicU64,
+    pub validation_failures: AtomicU64,
+    logs: Mutex<VecDeque<RequestLog>>,
+}
+
+impl UIState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn add_request_log(&self, log: RequestLog) {
+        let mut logs = self.logs.lock().await;
+        if logs.len() >= MAX_LOG_ENTRIES {
+            logs.pop_front();
+        }
+        logs.push_back(log);
+    }
+
+    pub async fn recent_logs(&self, limit: usize) -> Vec<RequestLog> {
+        let logs = self.logs.lock().await;
+        logs.iter().rev().take(limit).cloned().collect()
+    }
+}
@@ -0,0 +1,33 @@
+//! # Input validation
+//!
+//! Baseline request validation stage. `InputValidator::validate_request`
+//! performs no checks of its own and always returns `Ok(())`. Content
+//! decoding lives in `advanced_validation` and `content_type`.
+
+use crate::config::ValidationConfig;
+use crate::core::{SecurityContext, SecurityResult};
+use http::Request;
+
+/// The pipeline's input-validation stage; it applies no constraints.
+pub struct InputValidator {
+    config: ValidationConfig,
+}
+
+impl InputValidator {
+    pub fn new(config: ValidationConfig) -> Self {
+        Self { config }
+    }
+
+    /// Validate a request. Always returns `Ok(())` and leaves `context`
+    /// unchanged.
+    pub async fn validate_request<B>(
+        &self,
+        _request: &Request<B>,
+        _context: &mut SecurityContext,
+    ) -> SecurityResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        Ok(())
+    }
+}